@@ -1,17 +1,49 @@
-use std::env;
+use std::{env, error::Error, process::ExitCode};
 
-use clap::{command, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
 
 use crate::{
-    intl::run::run_extract,
-    translate::{tencent::TencentPayload, translate::Translate},
+    config::{Config, TranslateConfig},
+    intl::{
+        cache::DEFAULT_CACHE_FILE,
+        diagnostic::DiagnosticKind,
+        extract::{ExtractOptions, IntlInfo, SyntaxKind},
+        render::{emit_diagnostics, ColorChoice},
+        report::{write_report, ReportFormat},
+        run::{list_extract_files, run_extract, RunOptions},
+        watch::watch_extract,
+    },
+    translate::{
+        aws::{AwsProvider, DEFAULT_REGION as AWS_REGION},
+        azure::{AzureProvider, DEFAULT_ENDPOINT as AZURE_ENDPOINT},
+        credential::{
+            load_aws_credential, load_azure_key, load_deepl_auth_key, load_google_access_token,
+            load_openai_api_key, load_tencent_credential, DEFAULT_PROFILE,
+        },
+        deepl::{DeeplOptions, DeeplProvider, Formality, TagHandling},
+        google::{
+            GoogleProvider, DEFAULT_ENDPOINT as GOOGLE_ENDPOINT,
+            DEFAULT_LOCATION as GOOGLE_LOCATION,
+        },
+        openai::{OpenAiProvider, DEFAULT_ENDPOINT as OPENAI_ENDPOINT, DEFAULT_MODEL},
+        provider::TranslationProvider,
+        retry::{RequestPolicy, DEFAULT_RETRIES, DEFAULT_RPS},
+        tencent::{TencentEndpoint, TencentProvider, DEFAULT_ENDPOINT, DEFAULT_REGION},
+        translate::{Provider, Translate, TranslateResult},
+    },
 };
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
-
 pub struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "Config file path, by default intl.config.toml or .intlrc.json is looked up from the working directory upwards"
+    )]
+    config: Option<String>,
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -20,74 +52,855 @@ pub struct Cli {
 pub enum Commands {
     /// extract i18n text from files
     Extract {
-        #[arg(short, long, help = "Output file path",default_values = ["output.json"])]
+        #[arg(
+            short,
+            long,
+            help = "Directories to extract from, relative to the working directory [default: .]"
+        )]
+        root: Option<Vec<String>>,
+        #[arg(
+            long,
+            help = "Also extract files ignored by .gitignore and .ignore files",
+            overrides_with = "ignore"
+        )]
+        no_ignore: bool,
+        #[arg(
+            long,
+            help = "Skip files ignored by .gitignore and .ignore files, overrides no_ignore of the config file",
+            overrides_with = "no_ignore"
+        )]
+        ignore: bool,
+        #[arg(short, long, help = "Output file path [default: output.json]")]
         output: Option<String>,
-        #[arg(short, long, help = "Exclude files glob patterns", default_values = ["**/node_modules/**", "**/.git/**"])]
+        #[arg(
+            short,
+            long,
+            help = "Exclude files glob patterns, relative to the root [default: **/node_modules/** **/.git/**]"
+        )]
         excludes: Option<Vec<String>>,
-        #[arg(short, long, help = "Include files glob patterns", default_values = ["*.{ts,tsx}"])]
+        #[arg(
+            short,
+            long,
+            help = "Include files glob patterns, relative to the root [default: *.{ts,tsx}]"
+        )]
         includes: Option<Vec<String>>,
         #[arg(
             short,
             long,
-            help = "Use -d to Delate unreached key and value pairs in output"
+            help = "Translation function names, e.g. $t, t, i18n.t, this.$t, *.$t [default: $t]",
+            value_delimiter = ','
+        )]
+        functions: Option<Vec<String>>,
+        #[arg(
+            short,
+            long,
+            help = "Translation component names, e.g. Trans, FormattedMessage [default: Trans,FormattedMessage]",
+            value_delimiter = ','
+        )]
+        components: Option<Vec<String>>,
+        #[arg(long, help = "Override parser syntax (js, jsx, ts, tsx, dts) by file extension, e.g. js=js, ts=tsx", value_parser = parse_syntax)]
+        syntax: Vec<(String, SyntaxKind)>,
+        #[arg(
+            short,
+            long,
+            help = "Use -d to Delate unreached key and value pairs in output",
+            overrides_with = "no_delete_unreached"
         )]
         delete_unreached: bool,
+        #[arg(
+            long,
+            help = "Keep unreached key and value pairs in output, overrides delete_unreached of the config file",
+            overrides_with = "delete_unreached"
+        )]
+        no_delete_unreached: bool,
+        #[arg(
+            long,
+            help = "Exit with a non-zero code when a file can not be loaded or parsed, same as --fail-on parse-error"
+        )]
+        strict: bool,
+        #[arg(
+            long,
+            help = "Exit with a non-zero code when any of these problems is found",
+            value_delimiter = ','
+        )]
+        fail_on: Vec<FailOn>,
+        #[arg(long, help = "Write the problems found to a report file")]
+        report: Option<String>,
+        #[arg(long, help = "Report file format [default: json]", value_enum)]
+        report_format: Option<ReportFormat>,
+        #[arg(
+            long,
+            help = "When to use colors in diagnostics [default: auto]",
+            value_enum
+        )]
+        color: Option<ColorChoice>,
+        #[arg(
+            long,
+            help = "Print one line per problem instead of source code frames",
+            overrides_with = "no_compact"
+        )]
+        compact: bool,
+        #[arg(
+            long,
+            help = "Print source code frames, overrides compact of the config file",
+            overrides_with = "compact"
+        )]
+        no_compact: bool,
+        #[arg(
+            short,
+            long,
+            help = "Number of files parsed in parallel, defaults to the number of CPU cores"
+        )]
+        jobs: Option<usize>,
+        #[arg(
+            long,
+            help = "Keep the extraction results in a cache file, unchanged files are not parsed again [default file: .intl-cache]",
+            num_args = 0..=1,
+            default_missing_value = DEFAULT_CACHE_FILE
+        )]
+        cache: Option<String>,
+        #[arg(
+            long,
+            help = "Parse every file, without reading or writing the cache, also when cache is set in the config file"
+        )]
+        no_cache: bool,
+        #[arg(
+            short,
+            long,
+            help = "Keep running and extract files again when they change"
+        )]
+        watch: bool,
+        #[arg(
+            long,
+            help = "Print the files that would be extracted, without extracting them"
+        )]
+        list_files: bool,
+    },
+
+    /// translate the extracted texts with a translation service
+    Translate {
+        #[arg(long, help = "Translation service [default: tencent]", value_enum)]
+        provider: Option<Provider>,
+        #[command(flatten)]
+        args: TranslateArgs,
+        #[command(flatten)]
+        providers: Box<ProviderArgs>,
     },
 
+    /// translate the extracted texts with Tencent Cloud, same as translate --provider tencent
     TencentTranslate {
-        #[arg(short, long, help = "Input file path", default_value = "output.json")]
-        input: String,
-        #[arg(short, long, help = "Output file path")]
-        output: String,
-        #[arg(short, long, help = "source language", default_value = "zh")]
-        source: Option<String>,
-        #[arg(short, long, help = "target language", default_value = "en")]
-        target: Option<String>,
-        #[arg(short, long, help = "project id", default_value = "0")]
-        project_id: Option<u32>,
-        #[arg(short('d'), long, help = "secret_id")]
-        secret_id: String,
-        #[arg(short('k'), long, help = "secret_key")]
-        secret_key: String,
-        #[arg(short, long, help = "Translate and write all from input to output")]
-        write_all: bool,
+        #[command(flatten)]
+        args: TranslateArgs,
+        #[command(flatten)]
+        tencent: TencentArgs,
     },
 }
 
-pub fn run_cli() {
+// Options of the translation services, only the ones of the chosen service are used
+#[derive(Args, Default)]
+pub struct ProviderArgs {
+    #[command(flatten, next_help_heading = "Tencent options")]
+    tencent: TencentProviderArgs,
+    #[command(flatten, next_help_heading = "DeepL options")]
+    deepl: DeeplArgs,
+    #[command(flatten, next_help_heading = "OpenAI options")]
+    openai: OpenAiArgs,
+    #[command(flatten, next_help_heading = "Google options")]
+    google: GoogleArgs,
+    #[command(flatten, next_help_heading = "Azure options")]
+    azure: AzureArgs,
+    #[command(flatten, next_help_heading = "AWS options")]
+    aws: AwsArgs,
+}
+
+// Options of every translation service
+#[derive(Args)]
+pub struct TranslateArgs {
+    #[arg(short, long, help = "Input file path [default: output.json]")]
+    input: Option<String>,
+    #[arg(short, long, help = "Output file path")]
+    output: Option<String>,
+    #[arg(short, long, help = "source language [default: zh]")]
+    source: Option<String>,
+    #[arg(short, long, help = "target language [default: en]")]
+    target: Option<String>,
+    #[arg(
+        long,
+        help = "Retries of a request that failed with a rate limit, server or network error [default: 5]"
+    )]
+    retries: Option<u32>,
+    #[arg(long, help = "Requests sent per second at most [default: 5]", value_parser = parse_rps)]
+    rps: Option<f64>,
+    #[arg(short, long, help = "Translate and write all from input to output")]
+    write_all: bool,
+}
+
+#[derive(Args, Default)]
+pub struct DeeplArgs {
+    #[arg(
+        long,
+        help = "DeepL API key, prefer the DEEPL_AUTH_KEY environment variable"
+    )]
+    deepl_auth_key: Option<String>,
+    #[arg(
+        long,
+        help = "Endpoint URL of DeepL, by default the free or pro endpoint by the key [default: https://api.deepl.com/v2/translate]"
+    )]
+    deepl_endpoint: Option<String>,
+    #[arg(long, help = "Formality of the translation", value_enum)]
+    formality: Option<Formality>,
+    #[arg(
+        long,
+        help = "Glossary id, the glossary has to be made for the source and target language"
+    )]
+    glossary_id: Option<String>,
+    #[arg(long, help = "Keep the tags of the texts", value_enum)]
+    tag_handling: Option<TagHandling>,
+}
+
+#[derive(Args, Default)]
+pub struct OpenAiArgs {
+    #[arg(long, help = "API key, prefer the OPENAI_API_KEY environment variable")]
+    openai_api_key: Option<String>,
+    #[arg(
+        long,
+        help = "Base URL of an OpenAI compatible API, e.g. http://localhost:11434/v1 for Ollama [default: https://api.openai.com/v1]"
+    )]
+    openai_endpoint: Option<String>,
+    #[arg(long, help = "Model name [default: gpt-4o-mini]")]
+    model: Option<String>,
+    #[arg(
+        long,
+        help = "What the texts are about, e.g. \"checkout page of an online shop\""
+    )]
+    context: Option<String>,
+}
+
+#[derive(Args, Default)]
+pub struct GoogleArgs {
+    #[arg(
+        long,
+        help = "Google Cloud project id, by default the GOOGLE_CLOUD_PROJECT environment variable"
+    )]
+    google_project: Option<String>,
+    #[arg(long, help = "Location of the translation service [default: global]")]
+    google_location: Option<String>,
+    #[arg(
+        long,
+        help = "OAuth access token, prefer the GOOGLE_OAUTH_ACCESS_TOKEN environment variable, by default `gcloud auth print-access-token`"
+    )]
+    google_access_token: Option<String>,
+    #[arg(
+        long,
+        help = "Endpoint URL of the translation service [default: https://translation.googleapis.com]"
+    )]
+    google_endpoint: Option<String>,
+}
+
+#[derive(Args, Default)]
+pub struct AzureArgs {
+    #[arg(
+        long,
+        help = "Key of the Translator resource, prefer the AZURE_TRANSLATOR_KEY environment variable"
+    )]
+    azure_key: Option<String>,
+    #[arg(
+        long,
+        help = "Region of the Translator resource, e.g. westeurope, needed unless it is global"
+    )]
+    azure_region: Option<String>,
+    #[arg(
+        long,
+        help = "Endpoint URL of the translation service [default: https://api.cognitive.microsofttranslator.com]"
+    )]
+    azure_endpoint: Option<String>,
+}
+
+#[derive(Args, Default)]
+pub struct AwsArgs {
+    #[arg(
+        long,
+        help = "Profile of the AWS CLI credential file ~/.aws/credentials, used without the AWS_ACCESS_KEY_ID environment variable [default: default]"
+    )]
+    aws_profile: Option<String>,
+    #[arg(
+        long,
+        help = "Region of the translation service, by default the AWS_REGION environment variable [default: us-east-1]"
+    )]
+    aws_region: Option<String>,
+    #[arg(
+        long,
+        help = "Endpoint URL of the translation service, by default https://translate.<REGION>.amazonaws.com"
+    )]
+    aws_endpoint: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TencentArgs {
+    #[arg(short, long, help = "project id [default: 0]")]
+    project_id: Option<u32>,
+    #[arg(
+        short('d'),
+        long,
+        help = "Secret ID, prefer the TENCENTCLOUD_SECRET_ID environment variable"
+    )]
+    secret_id: Option<String>,
+    #[arg(
+        short('k'),
+        long,
+        help = "Secret Key, prefer the TENCENTCLOUD_SECRET_KEY environment variable"
+    )]
+    secret_key: Option<String>,
+    #[arg(
+        long,
+        help = "Profile of the Tencent Cloud CLI credential file ~/.tccli/<PROFILE>.credential [default: default]"
+    )]
+    profile: Option<String>,
+    #[arg(
+        long,
+        help = "Session token of temporary credentials, prefer the TENCENTCLOUD_SESSION_TOKEN environment variable"
+    )]
+    token: Option<String>,
+    #[arg(
+        long,
+        help = "Region of the translation service [default: ap-shanghai]"
+    )]
+    region: Option<String>,
+    #[arg(
+        long,
+        help = "Endpoint URL of the translation service [default: https://tmt.tencentcloudapi.com]"
+    )]
+    endpoint: Option<String>,
+}
+
+// Tencent options of `translate`, prefixed like the options of the other services
+#[derive(Args, Default)]
+pub struct TencentProviderArgs {
+    #[arg(long, help = "Tencent Cloud project id [default: 0]")]
+    tencent_project_id: Option<u32>,
+    #[arg(
+        long,
+        help = "Secret ID, prefer the TENCENTCLOUD_SECRET_ID environment variable"
+    )]
+    tencent_secret_id: Option<String>,
+    #[arg(
+        long,
+        help = "Secret Key, prefer the TENCENTCLOUD_SECRET_KEY environment variable"
+    )]
+    tencent_secret_key: Option<String>,
+    #[arg(
+        long,
+        help = "Profile of the Tencent Cloud CLI credential file ~/.tccli/<PROFILE>.credential [default: default]"
+    )]
+    tencent_profile: Option<String>,
+    #[arg(
+        long,
+        help = "Session token of temporary credentials, prefer the TENCENTCLOUD_SESSION_TOKEN environment variable"
+    )]
+    tencent_token: Option<String>,
+    #[arg(
+        long,
+        help = "Region of the translation service [default: ap-shanghai]"
+    )]
+    tencent_region: Option<String>,
+    #[arg(
+        long,
+        help = "Endpoint URL of the translation service [default: https://tmt.tencentcloudapi.com]"
+    )]
+    tencent_endpoint: Option<String>,
+}
+
+// `tencent-translate` only has Tencent options, they keep their short names
+impl From<TencentArgs> for TencentProviderArgs {
+    fn from(tencent: TencentArgs) -> TencentProviderArgs {
+        TencentProviderArgs {
+            tencent_project_id: tencent.project_id,
+            tencent_secret_id: tencent.secret_id,
+            tencent_secret_key: tencent.secret_key,
+            tencent_profile: tencent.profile,
+            tencent_token: tencent.token,
+            tencent_region: tencent.region,
+            tencent_endpoint: tencent.endpoint,
+        }
+    }
+}
+
+// Exit codes, 2 is used by clap for invalid arguments
+const EXIT_FAILURE: u8 = 1;
+const EXIT_PROBLEMS_FOUND: u8 = 3;
+
+// Defaults of the options that can also be set in the config file
+const DEFAULT_OUTPUT: &str = "output.json";
+const DEFAULT_EXCLUDES: [&str; 2] = ["**/node_modules/**", "**/.git/**"];
+const DEFAULT_INCLUDES: [&str; 1] = ["*.{ts,tsx}"];
+const DEFAULT_FUNCTIONS: [&str; 1] = ["$t"];
+const DEFAULT_COMPONENTS: [&str; 2] = ["Trans", "FormattedMessage"];
+const DEFAULT_SOURCE: &str = "zh";
+const DEFAULT_TARGET: &str = "en";
+
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailOn {
+    /// key or default text is not a string literal
+    NotLiteral,
+    /// same key with different value, in the sources or in the output file
+    Conflict,
+    /// translation function called without arguments
+    EmptyArgs,
+    /// file can not be loaded or parsed
+    ParseError,
+}
+
+impl FailOn {
+    fn kinds(&self) -> &[DiagnosticKind] {
+        match self {
+            FailOn::NotLiteral => &[DiagnosticKind::NotLiteral],
+            FailOn::Conflict => &[DiagnosticKind::Conflict, DiagnosticKind::ConflictWithOutput],
+            FailOn::EmptyArgs => &[DiagnosticKind::EmptyArgs],
+            FailOn::ParseError => &[DiagnosticKind::ParseError],
+        }
+    }
+}
+
+// Command line values win over config values, which win over the defaults
+fn get_values(
+    cli: Option<Vec<String>>,
+    config: Option<Vec<String>>,
+    default: &[&str],
+) -> Vec<String> {
+    cli.or(config)
+        .unwrap_or_else(|| default.iter().map(|value| value.to_string()).collect())
+}
+
+// `--flag` and `--no-flag` win over the config value, the last one given is used
+fn get_flag(flag: bool, no_flag: bool, config: Option<bool>) -> bool {
+    if flag || no_flag {
+        flag
+    } else {
+        config.unwrap_or(false)
+    }
+}
+
+fn parse_rps(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rps) if rps > 0.0 => Ok(rps),
+        _ => Err(format!(
+            "invalid rps '{}', expected a number greater than 0",
+            s
+        )),
+    }
+}
+
+fn parse_syntax(s: &str) -> Result<(String, SyntaxKind), String> {
+    let (ext, kind) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid syntax override '{}', expected EXT=SYNTAX", s))?;
+    Ok((ext.trim_start_matches('.').to_string(), kind.parse()?))
+}
+
+// Print the problems and the summary of an extraction, and write the report if requested
+fn print_extract(
+    intl_map: &IntlInfo,
+    color: ColorChoice,
+    compact: bool,
+    report: &Option<(String, ReportFormat)>,
+) -> Result<(), Box<dyn Error>> {
+    emit_diagnostics(intl_map, color, compact);
+    println!("{}", intl_map);
+    if let Some((report, report_format)) = report {
+        let current_dir = env::current_dir()?;
+        write_report(
+            &current_dir.join(report),
+            *report_format,
+            intl_map,
+            &current_dir,
+        )?;
+    }
+    Ok(())
+}
+
+fn get_tencent_provider(
+    tencent: TencentProviderArgs,
+    translate_config: &TranslateConfig,
+) -> Result<TencentProvider, Box<dyn Error>> {
+    let profile = tencent
+        .tencent_profile
+        .or_else(|| translate_config.tencent_profile.clone())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    let credential = load_tencent_credential(
+        tencent.tencent_secret_id,
+        tencent.tencent_secret_key,
+        tencent.tencent_token,
+        &profile,
+    )?;
+    let endpoint = TencentEndpoint::new(
+        &tencent
+            .tencent_endpoint
+            .or_else(|| translate_config.tencent_endpoint.clone())
+            .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string()),
+        tencent
+            .tencent_region
+            .or_else(|| translate_config.tencent_region.clone())
+            .unwrap_or_else(|| DEFAULT_REGION.to_string()),
+    )?;
+    let project_id = tencent
+        .tencent_project_id
+        .or(translate_config.tencent_project_id)
+        .unwrap_or(0);
+    Ok(TencentProvider::new(credential, endpoint, project_id))
+}
+
+fn get_deepl_provider(
+    deepl: DeeplArgs,
+    translate_config: &TranslateConfig,
+) -> Result<DeeplProvider, Box<dyn Error>> {
+    let auth_key = load_deepl_auth_key(deepl.deepl_auth_key)?;
+    let endpoint = deepl
+        .deepl_endpoint
+        .or_else(|| translate_config.deepl_endpoint.clone());
+    let options = DeeplOptions {
+        formality: deepl.formality.or(translate_config.formality),
+        glossary_id: deepl
+            .glossary_id
+            .or_else(|| translate_config.glossary_id.clone()),
+        tag_handling: deepl.tag_handling.or(translate_config.tag_handling),
+    };
+    DeeplProvider::new(auth_key, endpoint.as_deref(), options)
+}
+
+fn get_openai_provider(
+    openai: OpenAiArgs,
+    translate_config: &TranslateConfig,
+) -> Result<OpenAiProvider, Box<dyn Error>> {
+    OpenAiProvider::new(
+        load_openai_api_key(openai.openai_api_key),
+        &openai
+            .openai_endpoint
+            .or_else(|| translate_config.openai_endpoint.clone())
+            .unwrap_or_else(|| OPENAI_ENDPOINT.to_string()),
+        openai
+            .model
+            .or_else(|| translate_config.model.clone())
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+        openai.context.or_else(|| translate_config.context.clone()),
+    )
+}
+
+fn get_google_provider(
+    google: GoogleArgs,
+    translate_config: &TranslateConfig,
+) -> Result<GoogleProvider, Box<dyn Error>> {
+    let project = google
+        .google_project
+        .or_else(|| translate_config.google_project.clone())
+        .or_else(|| env::var("GOOGLE_CLOUD_PROJECT").ok())
+        .filter(|project| !project.is_empty())
+        .ok_or("No Google Cloud project, pass --google-project or set GOOGLE_CLOUD_PROJECT")?;
+    GoogleProvider::new(
+        load_google_access_token(google.google_access_token)?,
+        project,
+        &google
+            .google_location
+            .or_else(|| translate_config.google_location.clone())
+            .unwrap_or_else(|| GOOGLE_LOCATION.to_string()),
+        &google
+            .google_endpoint
+            .or_else(|| translate_config.google_endpoint.clone())
+            .unwrap_or_else(|| GOOGLE_ENDPOINT.to_string()),
+    )
+}
+
+fn get_azure_provider(
+    azure: AzureArgs,
+    translate_config: &TranslateConfig,
+) -> Result<AzureProvider, Box<dyn Error>> {
+    AzureProvider::new(
+        load_azure_key(azure.azure_key)?,
+        azure
+            .azure_region
+            .or_else(|| translate_config.azure_region.clone()),
+        &azure
+            .azure_endpoint
+            .or_else(|| translate_config.azure_endpoint.clone())
+            .unwrap_or_else(|| AZURE_ENDPOINT.to_string()),
+    )
+}
+
+fn get_aws_provider(
+    aws: AwsArgs,
+    translate_config: &TranslateConfig,
+) -> Result<AwsProvider, Box<dyn Error>> {
+    let profile = aws
+        .aws_profile
+        .or_else(|| translate_config.aws_profile.clone())
+        .or_else(|| env::var("AWS_PROFILE").ok())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    let region = aws
+        .aws_region
+        .or_else(|| translate_config.aws_region.clone())
+        .or_else(|| env::var("AWS_REGION").ok())
+        .or_else(|| env::var("AWS_DEFAULT_REGION").ok())
+        .unwrap_or_else(|| AWS_REGION.to_string());
+    let endpoint = aws
+        .aws_endpoint
+        .or_else(|| translate_config.aws_endpoint.clone());
+    AwsProvider::new(load_aws_credential(&profile)?, region, endpoint.as_deref())
+}
+
+// The provider of the target language in the config file, e.g. `de = "deepl"`, case insensitive
+fn get_target_provider(translate_config: &TranslateConfig, target: &str) -> Option<Provider> {
+    translate_config
+        .providers
+        .iter()
+        .find(|(language, _)| language.eq_ignore_ascii_case(target))
+        .map(|(_, provider)| *provider)
+}
+
+// `provider` is the command line choice, without it the provider is taken from the config file
+fn run_translate(
+    provider: Option<Provider>,
+    args: TranslateArgs,
+    providers: ProviderArgs,
+    translate_config: &TranslateConfig,
+) -> Result<TranslateResult, Box<dyn Error>> {
+    let input = args
+        .input
+        .or_else(|| translate_config.input.clone())
+        .unwrap_or_else(|| DEFAULT_OUTPUT.to_string());
+    let output = args
+        .output
+        .or_else(|| translate_config.output.clone())
+        .ok_or(
+        "No output file, pass --output or set output in the [translate] section of the config file",
+    )?;
+    let source = args
+        .source
+        .or_else(|| translate_config.source.clone())
+        .unwrap_or_else(|| DEFAULT_SOURCE.to_string());
+    let target = args
+        .target
+        .or_else(|| translate_config.target.clone())
+        .unwrap_or_else(|| DEFAULT_TARGET.to_string());
+    let policy = RequestPolicy {
+        retries: args
+            .retries
+            .or(translate_config.retries)
+            .unwrap_or(DEFAULT_RETRIES),
+        rps: args.rps.or(translate_config.rps).unwrap_or(DEFAULT_RPS),
+    };
+    if policy.rps <= 0.0 || policy.rps.is_nan() {
+        return Err("rps must be greater than 0".into());
+    }
+    let provider = provider
+        .or_else(|| get_target_provider(translate_config, &target))
+        .or(translate_config.provider)
+        .unwrap_or(Provider::Tencent);
+    let provider: Box<dyn TranslationProvider> = match provider {
+        Provider::Tencent => Box::new(get_tencent_provider(providers.tencent, translate_config)?),
+        Provider::Deepl => Box::new(get_deepl_provider(providers.deepl, translate_config)?),
+        Provider::Openai => Box::new(get_openai_provider(providers.openai, translate_config)?),
+        Provider::Google => Box::new(get_google_provider(providers.google, translate_config)?),
+        Provider::Azure => Box::new(get_azure_provider(providers.azure, translate_config)?),
+        Provider::Aws => Box::new(get_aws_provider(providers.aws, translate_config)?),
+    };
+
+    let input_dir = env::current_dir()?.join(input);
+    let output_dir = env::current_dir()?.join(output);
+
+    let input_str = input_dir.to_str().unwrap();
+    let output_str = output_dir.to_str().unwrap();
+
+    let mut translate = Translate::new(
+        input_str.to_string(),
+        output_str.to_string(),
+        source,
+        target,
+    );
+    translate.run(provider.as_ref(), &policy, args.write_all)
+}
+
+fn print_translate(result: Result<TranslateResult, Box<dyn Error>>) -> ExitCode {
+    match result {
+        Ok(result) => {
+            if result.translated > 0 {
+                println!(
+                    "=========== Translate {:?} words, use amount {:?}===========",
+                    result.translated, result.used_amount
+                );
+            }
+            if result.skipped > 0 {
+                println!(
+                    "=========== Skip {:?} words, run again to translate them ===========",
+                    result.skipped
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+pub fn run_cli() -> ExitCode {
     let cli = Cli::parse();
+    let config = match Config::load(cli.config.as_deref(), &env::current_dir().unwrap()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
     match cli.command {
         Some(Commands::Extract {
+            root,
+            no_ignore,
+            ignore,
             output,
             excludes,
             includes,
+            functions,
+            components,
+            syntax,
             delete_unreached,
+            no_delete_unreached,
+            strict,
+            fail_on,
+            report,
+            report_format,
+            color,
+            compact,
+            no_compact,
+            jobs,
+            cache,
+            no_cache,
+            watch,
+            list_files,
         }) => {
-            run_extract(output, excludes, includes, delete_unreached);
+            let extract_config = config.extract;
+            let output_config = config.output;
+            let mut syntax_map = extract_config.syntax;
+            syntax_map.extend(syntax);
+            let options = ExtractOptions {
+                functions: get_values(functions, extract_config.functions, &DEFAULT_FUNCTIONS),
+                components: get_values(components, extract_config.components, &DEFAULT_COMPONENTS),
+                syntax: syntax_map,
+            };
+            let mut fail_on = match extract_config.fail_on {
+                Some(config_fail_on) if fail_on.is_empty() => config_fail_on,
+                _ => fail_on,
+            };
+            if strict {
+                fail_on.push(FailOn::ParseError);
+            }
+            let run_options = RunOptions {
+                roots: root.or(extract_config.roots),
+                no_ignore: get_flag(no_ignore, ignore, extract_config.no_ignore),
+                output: Some(
+                    output
+                        .or(extract_config.output)
+                        .unwrap_or_else(|| DEFAULT_OUTPUT.to_string()),
+                ),
+                excludes: Some(get_values(
+                    excludes,
+                    extract_config.excludes,
+                    &DEFAULT_EXCLUDES,
+                )),
+                includes: Some(get_values(
+                    includes,
+                    extract_config.includes,
+                    &DEFAULT_INCLUDES,
+                )),
+                delete_unreached: get_flag(
+                    delete_unreached,
+                    no_delete_unreached,
+                    extract_config.delete_unreached,
+                ),
+                jobs,
+                cache: if no_cache {
+                    None
+                } else {
+                    cache.or(extract_config.cache)
+                },
+            };
+            let color = color.or(output_config.color).unwrap_or(ColorChoice::Auto);
+            let compact = get_flag(compact, no_compact, output_config.compact);
+            let report_format = report_format
+                .or(output_config.report_format)
+                .unwrap_or(ReportFormat::Json);
+            let report = report.or(output_config.report);
+            if list_files {
+                return match list_extract_files(run_options, options) {
+                    Ok(files) => {
+                        let current_dir = env::current_dir().unwrap();
+                        for file in files {
+                            println!(
+                                "{}",
+                                file.strip_prefix(&current_dir).unwrap_or(&file).display()
+                            );
+                        }
+                        ExitCode::SUCCESS
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        ExitCode::from(EXIT_FAILURE)
+                    }
+                };
+            }
+            let report = report.map(|report| (report, report_format));
+            if watch {
+                let result = watch_extract(run_options, options, |intl_map| {
+                    if let Err(e) = print_extract(intl_map, color, compact, &report) {
+                        eprintln!("Error: Failed to write report: {}", e);
+                    }
+                    println!("\n Watching for changes, press Ctrl-C to stop.");
+                });
+                return match result {
+                    Ok(()) => ExitCode::SUCCESS,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        ExitCode::from(EXIT_FAILURE)
+                    }
+                };
+            }
+            match run_extract(run_options, options) {
+                Ok(intl_map) => {
+                    if let Err(e) = print_extract(&intl_map, color, compact, &report) {
+                        eprintln!("Error: Failed to write report: {}", e);
+                        return ExitCode::from(EXIT_FAILURE);
+                    }
+                    let failed = fail_on
+                        .iter()
+                        .any(|f| f.kinds().iter().any(|kind| intl_map.has_diagnostic(*kind)));
+                    if failed {
+                        ExitCode::from(EXIT_PROBLEMS_FOUND)
+                    } else {
+                        ExitCode::SUCCESS
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::from(EXIT_FAILURE)
+                }
+            }
         }
 
-        Some(Commands::TencentTranslate {
-            input,
-            output,
-            source,
-            target,
-            project_id,
-            secret_id,
-            secret_key,
-            write_all,
-        }) => {
-            let input_dir = env::current_dir().unwrap().join(input);
-            let output_dir = env::current_dir().unwrap().join(output);
+        Some(Commands::Translate {
+            provider,
+            args,
+            providers,
+        }) => print_translate(run_translate(provider, args, *providers, &config.translate)),
 
-            let input_str = input_dir.to_str().unwrap();
-            let output_str = output_dir.to_str().unwrap();
-
-            let payload =
-                TencentPayload::new(source.unwrap(), target.unwrap(), project_id.unwrap());
-            let mut translate =
-                Translate::new(input_str.to_string(), output_str.to_string(), payload);
-            let _ = translate.from_tencent(secret_id.as_str(), secret_key.as_str(), write_all);
-        }
-        _ => (),
+        Some(Commands::TencentTranslate { args, tencent }) => print_translate(run_translate(
+            Some(Provider::Tencent),
+            args,
+            ProviderArgs {
+                tencent: tencent.into(),
+                ..Default::default()
+            },
+            &config.translate,
+        )),
+        _ => ExitCode::SUCCESS,
     }
 }
@@ -0,0 +1,135 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::FailOn;
+use crate::intl::extract::SyntaxKind;
+use crate::intl::render::ColorChoice;
+use crate::intl::report::ReportFormat;
+use crate::translate::deepl::{Formality, TagHandling};
+use crate::translate::translate::Provider;
+
+// Looked up in the working directory and then in its parents, the first one found is used
+const CONFIG_FILES: [&str; 2] = ["intl.config.toml", ".intlrc.json"];
+
+// Project configuration, every value can be overridden by the matching command line flag.
+// Paths are relative to the directory of the config file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub extract: ExtractConfig,
+    pub translate: TranslateConfig,
+    pub output: OutputConfig,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExtractConfig {
+    pub roots: Option<Vec<String>>,
+    pub includes: Option<Vec<String>>,
+    pub excludes: Option<Vec<String>>,
+    pub functions: Option<Vec<String>>,
+    pub components: Option<Vec<String>>,
+    // parser syntax by file extension, e.g. `js = "jsx"`
+    pub syntax: HashMap<String, SyntaxKind>,
+    pub output: Option<String>,
+    pub delete_unreached: Option<bool>,
+    pub no_ignore: Option<bool>,
+    pub cache: Option<String>,
+    pub fail_on: Option<Vec<FailOn>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TranslateConfig {
+    pub provider: Option<Provider>,
+    pub input: Option<String>,
+    pub output: Option<String>,
+    pub source: Option<String>,
+    pub target: Option<String>,
+    pub tencent_project_id: Option<u32>,
+    // profile of the Tencent Cloud CLI credential file
+    pub tencent_profile: Option<String>,
+    pub tencent_region: Option<String>,
+    pub tencent_endpoint: Option<String>,
+    pub retries: Option<u32>,
+    pub rps: Option<f64>,
+    // provider by target language, e.g. `de = "deepl"`, used instead of `provider`
+    pub providers: HashMap<String, Provider>,
+    pub deepl_endpoint: Option<String>,
+    pub formality: Option<Formality>,
+    pub glossary_id: Option<String>,
+    pub tag_handling: Option<TagHandling>,
+    // base URL of an OpenAI compatible API
+    pub openai_endpoint: Option<String>,
+    pub model: Option<String>,
+    pub context: Option<String>,
+    // Google Cloud project id, `GOOGLE_CLOUD_PROJECT` by default
+    pub google_project: Option<String>,
+    pub google_location: Option<String>,
+    pub google_endpoint: Option<String>,
+    pub azure_region: Option<String>,
+    pub azure_endpoint: Option<String>,
+    // profile of the AWS CLI credential file
+    pub aws_profile: Option<String>,
+    pub aws_region: Option<String>,
+    pub aws_endpoint: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub report: Option<String>,
+    pub report_format: Option<ReportFormat>,
+    pub color: Option<ColorChoice>,
+    pub compact: Option<bool>,
+}
+
+fn find_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .flat_map(|dir| CONFIG_FILES.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+fn resolve_path(dir: &Path, path: &mut Option<String>) {
+    if let Some(p) = path {
+        *p = dir.join(&p).to_string_lossy().to_string();
+    }
+}
+
+impl Config {
+    // `path` is the `--config` flag, without it the nearest config file is used if any
+    pub fn load(path: Option<&str>, current_dir: &Path) -> Result<Config, Box<dyn Error>> {
+        let path = match path {
+            Some(path) => current_dir.join(path),
+            None => match find_config(current_dir) {
+                Some(path) => path,
+                None => return Ok(Config::default()),
+            },
+        };
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str::<Config>(&content).map_err(|e| e.to_string())
+        } else {
+            toml::from_str::<Config>(&content).map_err(|e| e.to_string())
+        };
+        let mut config =
+            parsed.map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+
+        let dir = path.parent().unwrap_or(current_dir);
+        if let Some(roots) = &mut config.extract.roots {
+            for root in roots.iter_mut() {
+                *root = dir.join(&root).to_string_lossy().to_string();
+            }
+        }
+        resolve_path(dir, &mut config.extract.output);
+        resolve_path(dir, &mut config.extract.cache);
+        resolve_path(dir, &mut config.translate.input);
+        resolve_path(dir, &mut config.translate.output);
+        resolve_path(dir, &mut config.output.report);
+        Ok(config)
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{from_reader, to_writer};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use super::extract::{get_extension, ExtractOptions, FileIntl};
+use crate::utils::hash::get_hash;
+
+pub const DEFAULT_CACHE_FILE: &str = ".intl-cache";
+
+// Extraction results keyed by the file extension and the sha256 of the file content, so unchanged files are not
+// parsed again. Entries are only valid for the options and the version they were made with.
+#[derive(Default, Serialize, Deserialize)]
+pub struct ExtractCache {
+    options: String,
+    files: BTreeMap<String, FileIntl>,
+}
+
+// Everything that changes what is extracted from the same content
+fn get_options_hash(options: &ExtractOptions) -> String {
+    let mut syntax: Vec<_> = options.syntax.iter().collect();
+    syntax.sort_by(|a, b| a.0.cmp(b.0));
+    get_hash(
+        format!(
+            "{}|{:?}|{:?}|{:?}",
+            env!("CARGO_PKG_VERSION"),
+            options.functions,
+            options.components,
+            syntax
+        )
+        .as_bytes(),
+    )
+}
+
+// The extension decides the syntax and the vue front-end, so the same content is parsed
+// differently in `a.ts` and `a.tsx`
+fn get_key(hash: &str, path: &Path) -> String {
+    format!("{}:{}", get_extension(path), hash)
+}
+
+// Cached results point at the file they were made from, which may be another file with the
+// same content, or the same file before it was moved
+fn set_file(file_intl: &mut FileIntl, path: &Path) {
+    for message in file_intl.messages.iter_mut() {
+        message.location.file = path.to_path_buf();
+    }
+    for diagnostic in file_intl.diagnostics.iter_mut() {
+        diagnostic.file = path.to_path_buf();
+    }
+}
+
+fn warn_unreadable(path: &Path, e: &dyn Display) {
+    eprintln!(
+        "Warning: Failed to read cache {}: {}, every file is parsed",
+        path.display(),
+        e
+    );
+}
+
+impl ExtractCache {
+    // A missing or outdated cache is treated as empty, an unreadable one too with a warning
+    pub fn load(path: &Path, options: &ExtractOptions) -> ExtractCache {
+        let options = get_options_hash(options);
+        let cache = match File::open(path) {
+            Ok(file) => match from_reader::<_, ExtractCache>(BufReader::new(file)) {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    warn_unreadable(path, &e);
+                    None
+                }
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => {
+                warn_unreadable(path, &e);
+                None
+            }
+        };
+        match cache {
+            Some(cache) if cache.options == options => cache,
+            _ => ExtractCache {
+                options,
+                files: BTreeMap::new(),
+            },
+        }
+    }
+
+    pub fn get(&self, hash: &str, path: &Path) -> Option<FileIntl> {
+        let mut file_intl = self.files.get(&get_key(hash, path))?.clone();
+        set_file(&mut file_intl, path);
+        Some(file_intl)
+    }
+
+    // Only the current files are kept, entries of changed or deleted files are dropped
+    pub fn update<'a>(
+        &mut self,
+        file_intl_list: impl Iterator<Item = (&'a PathBuf, &'a (Option<String>, FileIntl))>,
+    ) {
+        self.files.clear();
+        for (path, (hash, file_intl)) in file_intl_list {
+            if let Some(hash) = hash {
+                // `get` sets the path again, and paths that are not UTF-8 can not be saved
+                let mut file_intl = file_intl.clone();
+                set_file(&mut file_intl, Path::new(""));
+                self.files.insert(get_key(hash, path), file_intl);
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        to_writer(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
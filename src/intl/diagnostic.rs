@@ -0,0 +1,231 @@
+use std::fmt;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use swc_common::{SourceMap, SourceMapper, Span};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DiagnosticKind {
+    // key or default text is not a string literal
+    NotLiteral,
+    // same key with different value in the sources
+    Conflict,
+    // same key with different value exists in output file
+    ConflictWithOutput,
+    // args is empty
+    EmptyArgs,
+    // file can not be loaded or parsed
+    ParseError,
+}
+
+impl DiagnosticKind {
+    pub const ALL: [DiagnosticKind; 5] = [
+        DiagnosticKind::NotLiteral,
+        DiagnosticKind::Conflict,
+        DiagnosticKind::ConflictWithOutput,
+        DiagnosticKind::EmptyArgs,
+        DiagnosticKind::ParseError,
+    ];
+
+    // stable id used in reports, matches the `--fail-on` values
+    pub fn id(&self) -> &'static str {
+        match self {
+            DiagnosticKind::NotLiteral => "not-literal",
+            DiagnosticKind::Conflict => "conflict",
+            DiagnosticKind::ConflictWithOutput => "conflict-with-output",
+            DiagnosticKind::EmptyArgs => "empty-args",
+            DiagnosticKind::ParseError => "parse-error",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            DiagnosticKind::NotLiteral => "Not a string literal",
+            DiagnosticKind::Conflict => "Same key with different value",
+            DiagnosticKind::ConflictWithOutput => "Same key with different value from existed file",
+            DiagnosticKind::EmptyArgs => "Args is empty",
+            DiagnosticKind::ParseError => "Parse error",
+        }
+    }
+
+    // label under the underlined source
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiagnosticKind::NotLiteral => "not a static string",
+            DiagnosticKind::Conflict => "different from the first use of this key",
+            DiagnosticKind::ConflictWithOutput => "different from the value in the output file",
+            DiagnosticKind::EmptyArgs => "no message key",
+            DiagnosticKind::ParseError => "here",
+        }
+    }
+
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            DiagnosticKind::NotLiteral => Some(
+                "pass the key as a string literal, e.g. $t('key'), dynamic keys can not be extracted",
+            ),
+            DiagnosticKind::Conflict => {
+                Some("use the same default text for every use of the key, or use different keys")
+            }
+            DiagnosticKind::ConflictWithOutput => {
+                Some("update the value in the output file, or change the default text here")
+            }
+            DiagnosticKind::EmptyArgs => Some("pass the message key as the first argument"),
+            DiagnosticKind::ParseError => None,
+        }
+    }
+
+    // the output file is usually edited by hand, a different value there is worth a look but
+    // does not make the extracted messages wrong
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticKind::ConflictWithOutput => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+// 1-based line and column, columns count UTF-16 code units like swc does
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    // byte offset from the start of the file
+    pub offset: usize,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+// Where a message was found, kept after the source map of the file is dropped so problems
+// found when merging files can still point at the source
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Location {
+    pub file: PathBuf,
+    pub range: Option<Range>,
+    pub snippet: Option<String>,
+}
+
+impl Location {
+    pub fn from_span(cm: &SourceMap, span: Span) -> Location {
+        let lo = cm.lookup_char_pos(span.lo);
+        let hi = cm.lookup_char_pos(span.hi);
+        Location {
+            file: PathBuf::from(lo.file.name.to_string()),
+            range: Some(Range {
+                start: Position {
+                    line: lo.line,
+                    column: lo.col.0 + 1,
+                    offset: (span.lo - lo.file.start_pos).0 as usize,
+                },
+                end: Position {
+                    line: hi.line,
+                    column: hi.col.0 + 1,
+                    offset: (span.hi - hi.file.start_pos).0 as usize,
+                },
+            }),
+            snippet: cm.span_to_snippet(span).ok(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub severity: Severity,
+    pub message: String,
+    pub file: PathBuf,
+    pub range: Option<Range>,
+    pub key: Option<String>,
+    // source text of the range, e.g. `$t(name)`
+    pub snippet: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, message: String, file: PathBuf) -> Diagnostic {
+        Diagnostic {
+            kind,
+            severity: kind.severity(),
+            message,
+            file,
+            range: None,
+            key: None,
+            snippet: None,
+        }
+    }
+
+    pub fn with_span(
+        cm: &SourceMap,
+        kind: DiagnosticKind,
+        message: String,
+        key: Option<&str>,
+        span: Span,
+    ) -> Diagnostic {
+        Diagnostic::at(kind, message, key, Location::from_span(cm, span))
+    }
+
+    pub fn at(
+        kind: DiagnosticKind,
+        message: String,
+        key: Option<&str>,
+        location: Location,
+    ) -> Diagnostic {
+        Diagnostic {
+            kind,
+            severity: kind.severity(),
+            message,
+            file: location.file,
+            range: location.range,
+            key: key.map(|key| key.to_string()),
+            snippet: location.snippet,
+        }
+    }
+}
+
+impl Diagnostic {
+    // `src/a.ts:3:5`
+    pub fn location(&self) -> String {
+        match &self.range {
+            Some(range) => format!(
+                "{}:{}:{}",
+                self.file.display(),
+                range.start.line,
+                range.start.column
+            ),
+            None => self.file.display().to_string(),
+        }
+    }
+}
+
+// `src/a.ts:3:5: error[not-literal]: Not a string literal.`
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}[{}]: {}",
+            self.location(),
+            self.severity,
+            self.kind.id(),
+            self.message
+        )
+    }
+}
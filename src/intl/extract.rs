@@ -1,75 +1,152 @@
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::fmt::Display;
+use std::fs;
 use std::path::Path;
-use swc_common::errors::Handler;
+use std::str::FromStr;
 use swc_common::sync::Lrc;
-use swc_common::{errors::ColorConfig, SourceMap};
-use swc_ecma_ast::{CallExpr, Callee, ExprOrSpread};
-use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax, TsSyntax};
+use swc_common::{FileName, SourceMap, Span, Spanned};
+use swc_ecma_ast::{
+    BinaryOp, CallExpr, Callee, Expr, ExprOrSpread, JSXAttr, JSXAttrName, JSXAttrOrSpread,
+    JSXAttrValue, JSXElement, JSXElementChild, JSXElementName, JSXExpr, JSXObject, MemberProp,
+    ObjectLit, PropName,
+};
+use swc_ecma_parser::{lexer::Lexer, EsSyntax, Parser, StringInput, Syntax, TsSyntax};
 use swc_ecma_visit::{Visit, VisitWith};
 
-/**
- * 0:Not a string literal
- * 1: Same key with different value
- * 2: Same key with different value exists in output file
- * 3: args is empty
- */
-// const err_type_tup: (u8, u8, u8, u8) = (0, 1, 2, 3);
+use super::diagnostic::{Diagnostic, DiagnosticKind, Location};
+use super::vue::parse_sfc;
 
+#[derive(Default)]
 pub struct IntlInfo {
     pub info_map: HashMap<String, IntlOkInfo>,
     pub repeat_key_list: Vec<(String, Value)>,
-    pub err_map: HashMap<u8, Vec<IntlErrInfo>>,
+    pub diagnostics: BTreeMap<DiagnosticKind, Vec<Diagnostic>>,
+    // every key used in the sources with the default of its first use that has one, also
+    // for keys that are already in the output file
+    first_default_map: HashMap<String, Option<String>>,
 }
 
 impl Display for IntlInfo {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let add_num = self.info_map.len();
         let mut err_str = "".to_string();
-        if self.err_map.contains_key(&0) {
-            err_str.push_str("\n ============== Not a string literal =============\n");
-            self.err_map.get(&0).unwrap().iter().for_each(|e| {
-                err_str.push_str(format!("\n {};", e.err_msg).as_str());
-            });
-        }
-        if self.err_map.contains_key(&1) {
-            err_str.push_str("\n ============== Same key with different value =============\n");
-            self.err_map.get(&1).unwrap().iter().for_each(|e| {
-                err_str.push_str(format!("\n {};", e.err_msg).as_str());
-            });
-        }
-        if self.err_map.contains_key(&2) {
+        // every problem is rendered on stderr, only the counts are summarized here
+        for (kind, diagnostics) in self.diagnostics.iter() {
             err_str.push_str(
-                "\n ============== Same key with different value from existed file =============\n",
+                format!(
+                    "\n ============== {}: {} =============",
+                    kind.title(),
+                    diagnostics.len()
+                )
+                .as_str(),
             );
-            self.err_map.get(&2).unwrap().iter().for_each(|e| {
-                err_str.push_str(format!("\n {};", e.err_msg).as_str());
-            });
-        }
-        if self.err_map.contains_key(&3) {
-            err_str.push_str("\n ============== Args is empty  =============\n");
-            self.err_map.get(&3).unwrap().iter().for_each(|e| {
-                err_str.push_str(format!("\n {};", e.err_msg).as_str());
-            });
         }
-        return write!(
+        write!(
             f,
             "\n ************** Complete the extraction of {} pieces of text. **************\n{}",
             add_num, err_str
-        );
+        )
     }
 }
 
-pub struct IntlErrInfo {
-    pub err_type: u8,
-    pub err_msg: String,
-}
+impl IntlInfo {
+    fn add_diagnostic(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics
+            .entry(diagnostic.kind)
+            .or_default()
+            .push(diagnostic);
+    }
+
+    pub fn has_diagnostic(&self, kind: DiagnosticKind) -> bool {
+        self.diagnostics.contains_key(&kind)
+    }
+
+    // Files are merged in the same order they would be visited one by one, so the first use
+    // of a key wins and conflicts are reported at the later uses
+    pub fn merge(&mut self, file_intl: &FileIntl, existed_map: &Map<String, Value>) {
+        for diagnostic in file_intl.diagnostics.iter() {
+            self.add_diagnostic(diagnostic.clone());
+        }
+        for message in file_intl.messages.iter() {
+            self.add_intl(message, existed_map);
+        }
+    }
+
+    fn add_intl(&mut self, message: &IntlMessage, existed_map: &Map<String, Value>) {
+        let id = message.key.as_str();
+        let default_val = message.default.as_str();
+        // later uses are compared with the first explicit default, whether the key is in the
+        // output file or not. A use without a default never conflicts, it only falls back to
+        // the key until a use with a default is found.
+        match self.first_default_map.get(id) {
+            Some(_) if !message.has_default => return,
+            Some(Some(first_default)) => {
+                if first_default != default_val {
+                    let err_msg = format!(
+                        "Intl key: {} same key with different value '{}' and '{}'",
+                        id, first_default, default_val
+                    );
+                    self.add_diagnostic(Diagnostic::at(
+                        DiagnosticKind::Conflict,
+                        err_msg,
+                        Some(id),
+                        message.location.clone(),
+                    ));
+                }
+                return;
+            }
+            Some(None) => {
+                self.first_default_map
+                    .insert(id.to_string(), Some(default_val.to_string()));
+                match self.info_map.get_mut(id) {
+                    Some(info) => info.default = default_val.to_string(),
+                    None => self.check_existed(message, existed_map),
+                }
+                return;
+            }
+            None => (),
+        }
+        self.first_default_map.insert(
+            id.to_string(),
+            message.has_default.then(|| default_val.to_string()),
+        );
 
-impl IntlErrInfo {
-    fn new(err_type: u8, err_msg: String) -> IntlErrInfo {
-        IntlErrInfo { err_type, err_msg }
+        if let Some(existed_value) = existed_map.get(id) {
+            self.repeat_key_list
+                .push((id.to_string(), existed_value.clone()));
+            if message.has_default {
+                self.check_existed(message, existed_map);
+            }
+        } else {
+            self.info_map.insert(
+                id.to_string(),
+                IntlOkInfo {
+                    key: id.to_string(),
+                    default: default_val.to_string(),
+                },
+            );
+        }
+    }
+
+    fn check_existed(&mut self, message: &IntlMessage, existed_map: &Map<String, Value>) {
+        let id = message.key.as_str();
+        if let Some(existed_value) = existed_map.get(id) {
+            if existed_value != message.default.as_str() {
+                let err_msg = format!(
+                    "Intl key: {} same key with different value from existed file",
+                    id
+                );
+                self.add_diagnostic(Diagnostic::at(
+                    DiagnosticKind::ConflictWithOutput,
+                    err_msg,
+                    Some(id),
+                    message.location.clone(),
+                ));
+            }
+        }
     }
 }
 
@@ -79,166 +156,441 @@ pub struct IntlOkInfo {
     pub default: String,
 }
 
+// A message found in a file, in source order
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IntlMessage {
+    pub key: String,
+    // the key itself when the source gives no default
+    pub default: String,
+    pub has_default: bool,
+    pub location: Location,
+}
+
+// Everything extracted from one file, independent of the other files so files can be
+// extracted in parallel and merged afterwards
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FileIntl {
+    pub messages: Vec<IntlMessage>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
 // #[derive(Default)]
 struct TransformVisitor<'a> {
-    // already exist
-    existed_map: &'a Map<String, Value>,
     // visited
-    visited_intl: &'a mut IntlInfo,
+    visited_intl: &'a mut FileIntl,
     // current file path
     cm: &'a SourceMap,
+    // extract options
+    options: &'a ExtractOptions,
+}
+
+pub struct ExtractOptions {
+    // callee patterns treated as translation functions, e.g. `$t`, `i18n.t`, `*.$t`
+    pub functions: Vec<String>,
+    // component names whose props and children are messages, e.g. `Trans`, `FormattedMessage`
+    pub components: Vec<String>,
+    // parser syntax overrides keyed by file extension (or vue script `lang`), e.g. `js` => Jsx
+    pub syntax: HashMap<String, SyntaxKind>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyntaxKind {
+    Js,
+    Jsx,
+    Ts,
+    Tsx,
+    Dts,
+}
+
+impl FromStr for SyntaxKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "js" => Ok(SyntaxKind::Js),
+            "jsx" => Ok(SyntaxKind::Jsx),
+            "ts" => Ok(SyntaxKind::Ts),
+            "tsx" => Ok(SyntaxKind::Tsx),
+            "dts" => Ok(SyntaxKind::Dts),
+            _ => Err(format!(
+                "unknown syntax '{}', expected one of js, jsx, ts, tsx, dts",
+                s
+            )),
+        }
+    }
+}
+
+impl SyntaxKind {
+    // `.js` files are commonly React components, so JSX is enabled for them.
+    // Unknown extensions keep the TSX syntax used before syntaxes were selected per file.
+    fn from_extension(ext: &str) -> SyntaxKind {
+        match ext {
+            "js" | "jsx" => SyntaxKind::Jsx,
+            "mjs" | "cjs" => SyntaxKind::Js,
+            "ts" | "mts" | "cts" => SyntaxKind::Ts,
+            "d.ts" | "d.mts" | "d.cts" => SyntaxKind::Dts,
+            _ => SyntaxKind::Tsx,
+        }
+    }
+
+    fn to_syntax(self) -> Syntax {
+        match self {
+            SyntaxKind::Js | SyntaxKind::Jsx => Syntax::Es(EsSyntax {
+                jsx: self == SyntaxKind::Jsx,
+                decorators: true,
+                ..Default::default()
+            }),
+            SyntaxKind::Ts | SyntaxKind::Tsx | SyntaxKind::Dts => Syntax::Typescript(TsSyntax {
+                tsx: self == SyntaxKind::Tsx,
+                decorators: true,
+                dts: self == SyntaxKind::Dts,
+                no_early_errors: false,
+                disallow_ambiguous_jsx_like: false,
+            }),
+        }
+    }
+}
+
+// `a.d.ts` => "d.ts", `a.tsx` => "tsx"
+pub fn get_extension(path: &Path) -> &str {
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    let is_declaration = path
+        .file_stem()
+        .and_then(|stem| Path::new(stem).extension())
+        .is_some_and(|stem_ext| stem_ext == "d");
+    match (is_declaration, ext) {
+        (true, "ts") => "d.ts",
+        (true, "mts") => "d.mts",
+        (true, "cts") => "d.cts",
+        _ => ext,
+    }
+}
+
+fn get_syntax(ext: &str, options: &ExtractOptions) -> Syntax {
+    options
+        .syntax
+        .get(ext)
+        .copied()
+        .unwrap_or_else(|| SyntaxKind::from_extension(ext))
+        .to_syntax()
 }
 
 // 输出格式:
 // 全部XXX条,新增XXX条
 // 不是常量/不是字符串/参数为空
 
-fn get_caller_name(callee: &Callee) -> Option<&str> {
-    if callee.is_expr() {
-        let name = callee.as_expr()?.as_ident()?.sym.as_str();
-        return Some(name);
+fn get_caller_name(callee: &Callee) -> Option<String> {
+    get_expr_name(callee.as_expr()?)
+}
+
+// `$t` => "$t", `i18n.t` => "i18n.t", `this.$t` => "this.$t", `intl["formatMessage"]` => "intl.formatMessage"
+fn get_expr_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Ident(ident) => Some(ident.sym.to_string()),
+        Expr::This(_) => Some("this".to_string()),
+        Expr::Paren(paren) => get_expr_name(&paren.expr),
+        Expr::Member(member) => {
+            let obj = get_expr_name(&member.obj)?;
+            let prop = match &member.prop {
+                MemberProp::Ident(ident) => ident.sym.to_string(),
+                MemberProp::Computed(computed) => {
+                    computed.expr.as_lit()?.as_str()?.value.to_string()
+                }
+                MemberProp::PrivateName(_) => return None,
+            };
+            Some(format!("{}.{}", obj, prop))
+        }
+        _ => None,
     }
-    return None;
+}
+
+// `*` matches exactly one segment of the callee, e.g. `*.$t` matches `this.$t` and `vm.$t`
+fn is_match_function(pattern: &str, name: &str) -> bool {
+    let pattern_segments: Vec<&str> = pattern.split('.').collect();
+    let name_segments: Vec<&str> = name.split('.').collect();
+    pattern_segments.len() == name_segments.len()
+        && pattern_segments
+            .iter()
+            .zip(name_segments.iter())
+            .all(|(p, n)| *p == "*" || p == n)
 }
 
 struct ErrInfo {
-    err_type: u8,
+    kind: DiagnosticKind,
     err_msg: String,
 }
 
-fn get_intel_info(node: &CallExpr) -> Result<&str, ErrInfo> {
-    let args: &Vec<ExprOrSpread> = &node.args;
-    if args.len() >= 1 {
-        let args_0 = &args[0];
-        let option_lit = args_0.expr.as_lit();
-        if let Some(lit) = option_lit {
-            let option_str = lit.as_str();
-            if let Some(str) = option_str {
-                return Ok(&str.value);
-            } else {
+fn not_literal_err() -> ErrInfo {
+    ErrInfo {
+        kind: DiagnosticKind::NotLiteral,
+        err_msg: "Not a string literal.".to_string(),
+    }
+}
+
+// Constant-fold a string expression: `'text'`, `` `text` `` and `'a long ' + 'text'`
+fn get_str_value(expr: &Expr) -> Result<String, ErrInfo> {
+    match expr {
+        Expr::Lit(lit) => match lit.as_str() {
+            Some(str) => Ok(str.value.to_string()),
+            None => Err(not_literal_err()),
+        },
+        Expr::Tpl(tpl) => {
+            if !tpl.exprs.is_empty() {
                 return Err(ErrInfo {
-                    err_type: 0,
-                    err_msg: "Error: Not a string literal.".to_string(),
+                    kind: DiagnosticKind::NotLiteral,
+                    err_msg: "Template literal contains interpolation `${...}`, use a placeholder like {name} instead.".to_string(),
                 });
             }
+            tpl.quasis
+                .iter()
+                .map(|quasi| quasi.cooked.as_ref().map(|cooked| cooked.as_str()))
+                .collect::<Option<String>>()
+                .ok_or_else(not_literal_err)
+        }
+        Expr::Bin(bin) if bin.op == BinaryOp::Add => {
+            Ok(get_str_value(&bin.left)? + get_str_value(&bin.right)?.as_str())
+        }
+        Expr::Paren(paren) => get_str_value(&paren.expr),
+        _ => Err(not_literal_err()),
+    }
+}
+
+// Find the value of `name` in an object literal, e.g. `{ id: 'key', defaultMessage: 'text' }`
+fn get_object_value<'a>(obj: &'a ObjectLit, name: &str) -> Option<&'a Expr> {
+    obj.props.iter().find_map(|prop| {
+        let key_value = prop.as_prop()?.as_key_value()?;
+        let key = match &key_value.key {
+            PropName::Ident(ident) => ident.sym.as_str(),
+            PropName::Str(str) => str.value.as_str(),
+            _ => return None,
+        };
+        if key == name {
+            Some(&*key_value.value)
         } else {
-            return Err(ErrInfo {
-                err_type: 0,
-                err_msg: "Error: Not a string literal.".to_string(),
-            });
+            None
         }
-    } else {
+    })
+}
+
+// Returns (id, default value if given) of a translation call:
+// `$t('key')`, `$t('key', 'default')`, `$t('key', { defaultMessage: 'default' })`
+// and `formatMessage({ id: 'key', defaultMessage: 'default' })`
+fn get_intel_info(node: &CallExpr) -> Result<(String, Option<String>), ErrInfo> {
+    let args: &Vec<ExprOrSpread> = &node.args;
+    if args.is_empty() {
         return Err(ErrInfo {
-            err_type: 3,
-            err_msg: "Error: Args is empty.".to_string(),
+            kind: DiagnosticKind::EmptyArgs,
+            err_msg: "Args is empty.".to_string(),
+        });
+    }
+    if let Some(obj) = args[0].expr.as_object() {
+        let id = match get_object_value(obj, "id") {
+            Some(expr) => get_str_value(expr)?,
+            None => {
+                return Err(ErrInfo {
+                    kind: DiagnosticKind::EmptyArgs,
+                    err_msg: "Message descriptor has no id.".to_string(),
+                })
+            }
+        };
+        let default_val = match get_object_value(obj, "defaultMessage") {
+            Some(expr) => Some(get_str_value(expr)?),
+            None => None,
+        };
+        return Ok((id, default_val));
+    }
+
+    let id = get_str_value(&args[0].expr)?;
+    // The second argument is only a default when it constant-folds, other values like the
+    // plural count of `$t('key', count + 1)` fall back to the key
+    let default_val = match args.get(1).map(|arg| &*arg.expr) {
+        // `$t('key', { defaultMessage: 'default' })`, other objects are interpolation values
+        Some(Expr::Object(obj)) => {
+            get_object_value(obj, "defaultMessage").and_then(|expr| get_str_value(expr).ok())
+        }
+        // `$t('key', 'default')`
+        Some(expr) => get_str_value(expr).ok(),
+        None => None,
+    };
+    Ok((id, default_val))
+}
+
+fn get_jsx_object_name(obj: &JSXObject) -> String {
+    match obj {
+        JSXObject::Ident(ident) => ident.sym.to_string(),
+        JSXObject::JSXMemberExpr(member) => {
+            format!("{}.{}", get_jsx_object_name(&member.obj), member.prop.sym)
+        }
+    }
+}
+
+// `<Trans>` => "Trans", `<Intl.FormattedMessage>` => "Intl.FormattedMessage"
+fn get_jsx_element_name(name: &JSXElementName) -> Option<String> {
+    match name {
+        JSXElementName::Ident(ident) => Some(ident.sym.to_string()),
+        JSXElementName::JSXMemberExpr(member) => Some(format!(
+            "{}.{}",
+            get_jsx_object_name(&member.obj),
+            member.prop.sym
+        )),
+        JSXElementName::JSXNamespacedName(_) => None,
+    }
+}
+
+fn get_jsx_attr<'a>(node: &'a JSXElement, name: &str) -> Option<&'a JSXAttr> {
+    node.opening.attrs.iter().find_map(|attr| match attr {
+        JSXAttrOrSpread::JSXAttr(attr) => match &attr.name {
+            JSXAttrName::Ident(ident) if ident.sym == name => Some(attr),
+            _ => None,
+        },
+        JSXAttrOrSpread::SpreadElement(_) => None,
+    })
+}
+
+// `id="key"` and `id={'key'}`
+fn get_jsx_attr_value(attr: &JSXAttr) -> Result<String, ErrInfo> {
+    match &attr.value {
+        Some(JSXAttrValue::Lit(lit)) => match lit.as_str() {
+            Some(str) => Ok(str.value.to_string()),
+            None => Err(not_literal_err()),
+        },
+        Some(JSXAttrValue::JSXExprContainer(container)) => match &container.expr {
+            JSXExpr::Expr(expr) => get_str_value(expr),
+            JSXExpr::JSXEmptyExpr(_) => Err(not_literal_err()),
+        },
+        _ => Err(not_literal_err()),
+    }
+}
+
+// Text children with whitespace collapsed the way JSX renders them, e.g. `<Trans>Hello {'world'}</Trans>`
+fn get_jsx_children_text(children: &[JSXElementChild]) -> Result<Option<String>, ErrInfo> {
+    let mut text = String::new();
+    for child in children {
+        match child {
+            JSXElementChild::JSXText(jsx_text) => text.push_str(&jsx_text.value),
+            JSXElementChild::JSXExprContainer(container) => match &container.expr {
+                JSXExpr::Expr(expr) => {
+                    text.push_str(&get_str_value(expr).map_err(|_| ErrInfo {
+                        kind: DiagnosticKind::NotLiteral,
+                        err_msg: "Children is not a plain text.".to_string(),
+                    })?)
+                }
+                JSXExpr::JSXEmptyExpr(_) => (),
+            },
+            _ => {
+                return Err(ErrInfo {
+                    kind: DiagnosticKind::NotLiteral,
+                    err_msg: "Children is not a plain text.".to_string(),
+                })
+            }
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(text))
+    }
+}
+
+// Returns (id, default value if given) of a translation component:
+// `<FormattedMessage id="key" defaultMessage="default" />`, `<Trans i18nKey="key">default</Trans>`
+// and `<Trans>key</Trans>`
+fn get_jsx_intl_info(node: &JSXElement) -> Result<(String, Option<String>), ErrInfo> {
+    let id = match get_jsx_attr(node, "id").or_else(|| get_jsx_attr(node, "i18nKey")) {
+        Some(attr) => Some(get_jsx_attr_value(attr)?),
+        None => None,
+    };
+    let default_val = match get_jsx_attr(node, "defaultMessage") {
+        Some(attr) => Some(get_jsx_attr_value(attr)?),
+        None => None,
+    };
+    match (id, default_val) {
+        (Some(id), Some(default_val)) => Ok((id, Some(default_val))),
+        // children with nested elements are rendered by the component, fall back to the key
+        (Some(id), None) => match get_jsx_children_text(&node.children) {
+            Ok(Some(text)) => Ok((id, Some(text))),
+            _ => Ok((id, None)),
+        },
+        (None, default_val) => match get_jsx_children_text(&node.children)? {
+            Some(text) => Ok((text, default_val)),
+            None => Err(ErrInfo {
+                kind: DiagnosticKind::EmptyArgs,
+                err_msg: "Component has no id, i18nKey or text children.".to_string(),
+            }),
+        },
+    }
+}
+
+impl TransformVisitor<'_> {
+    fn add_err(&mut self, kind: DiagnosticKind, err_msg: String, key: Option<&str>, span: Span) {
+        let diagnostic = Diagnostic::with_span(self.cm, kind, err_msg, key, span);
+        self.visited_intl.diagnostics.push(diagnostic);
+    }
+
+    fn add_intl(&mut self, id: &str, default_val: Option<&str>, span: Span) {
+        self.visited_intl.messages.push(IntlMessage {
+            key: id.to_string(),
+            default: default_val.unwrap_or(id).to_string(),
+            has_default: default_val.is_some(),
+            location: Location::from_span(self.cm, span),
         });
     }
+
+    // `v-t="'key'"` and `v-t="{ path: 'key' }"` in vue templates
+    fn visit_vue_directive(&mut self, expr: &Expr) {
+        let value = match expr.unwrap_parens() {
+            Expr::Object(obj) => match get_object_value(obj, "path") {
+                Some(expr) => get_str_value(expr),
+                None => Err(ErrInfo {
+                    kind: DiagnosticKind::EmptyArgs,
+                    err_msg: "v-t directive has no path.".to_string(),
+                }),
+            },
+            expr => get_str_value(expr),
+        };
+        match value {
+            Ok(id) => self.add_intl(&id, None, expr.span()),
+            Err(e) => self.add_err(e.kind, e.err_msg, None, expr.span()),
+        }
+    }
 }
 
 impl Visit for TransformVisitor<'_> {
     fn visit_call_expr(&mut self, node: &CallExpr) {
         let callee = &node.callee;
         let name = get_caller_name(callee);
-        // let curr_path = &self.curr_path;
-        let detail_msg = format!(
-            " File is {file}; {line}",
-            file = self
-                .cm
-                .span_to_lines(node.span)
-                .unwrap()
-                .file
-                .name
-                .to_string(),
-            line = self
-                .cm
-                .span_to_lines(node.span)
-                .unwrap()
-                .lines
+
+        if let Some(n) = name {
+            if self
+                .options
+                .functions
                 .iter()
-                .map(|l| format!(
-                    "Line num: {index}, start col num: {start}, end col num: {end} ",
-                    index = l.line_index,
-                    start = l.start_col.0,
-                    end = l.end_col.0
-                ))
-                .collect::<Vec<_>>()
-                .join(";")
-        );
+                .any(|f| is_match_function(f, &n))
+            {
+                match get_intel_info(node) {
+                    Ok((id, default_val)) => self.add_intl(&id, default_val.as_deref(), node.span),
+                    Err(e) => self.add_err(e.kind, e.err_msg, None, node.span),
+                }
+            }
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_jsx_element(&mut self, node: &JSXElement) {
+        let name = get_jsx_element_name(&node.opening.name);
 
         if let Some(n) = name {
-            if n == "$t" {
-                let value = get_intel_info(node);
-                match value {
-                    Ok(v) => {
-                        // unique id
-                        let id = v;
-                        // default value
-                        let default_val = v;
-                        if self.visited_intl.info_map.contains_key(id) {
-                            let visited_intl = self.visited_intl.info_map.get(id).unwrap();
-                            if visited_intl.default != default_val {
-                                let err_msg = "Error: Intl key:".to_string()
-                                    + id
-                                    + " same key with different value"
-                                    + detail_msg.as_str();
-                                let error_info = IntlErrInfo::new(1, err_msg);
-                                if self.visited_intl.err_map.contains_key(&1) {
-                                    self.visited_intl
-                                        .err_map
-                                        .get_mut(&1)
-                                        .unwrap()
-                                        .push(error_info);
-                                } else {
-                                    self.visited_intl.err_map.insert(1, vec![error_info]);
-                                }
-                            }
-                        } else if self.existed_map.contains_key(id) {
-                            let existed_value = self.existed_map.get(id).unwrap();
-                            self.visited_intl
-                                .repeat_key_list
-                                .push((id.to_string(), existed_value.clone()));
-
-                            if existed_value != default_val {
-                                let err_msg = "Error: Intl key: ".to_string()
-                                    + id
-                                    + " same key with different value from existed file'"
-                                    + detail_msg.as_str();
-                                let error_info = IntlErrInfo::new(2, err_msg);
-                                if self.visited_intl.err_map.contains_key(&2) {
-                                    self.visited_intl
-                                        .err_map
-                                        .get_mut(&2)
-                                        .unwrap()
-                                        .push(error_info);
-                                } else {
-                                    self.visited_intl.err_map.insert(2, vec![error_info]);
-                                }
-                            }
-                        } else {
-                            self.visited_intl.info_map.insert(
-                                id.to_string(),
-                                IntlOkInfo {
-                                    key: id.to_string(),
-                                    default: default_val.to_string(),
-                                },
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        let error_info =
-                            IntlErrInfo::new(e.err_type, e.err_msg + detail_msg.as_str());
-                        if self.visited_intl.err_map.contains_key(&e.err_type) {
-                            self.visited_intl
-                                .err_map
-                                .get_mut(&e.err_type)
-                                .unwrap()
-                                .push(error_info);
-                        } else {
-                            self.visited_intl
-                                .err_map
-                                .insert(e.err_type, vec![error_info]);
-                        }
-                    }
+            if self
+                .options
+                .components
+                .iter()
+                .any(|c| is_match_function(c, &n))
+            {
+                match get_jsx_intl_info(node) {
+                    Ok((id, default_val)) => self.add_intl(&id, default_val.as_deref(), node.span),
+                    Err(e) => self.add_err(e.kind, e.err_msg, None, node.span),
                 }
             }
         }
@@ -246,25 +598,38 @@ impl Visit for TransformVisitor<'_> {
     }
 }
 
-pub fn extract_text<'a>(
-    path: &str,
-    existed_map: &'a Map<String, Value>,
-    extract_info: &mut IntlInfo,
-) {
+pub fn extract_text(path: &Path, options: &ExtractOptions) -> FileIntl {
+    let mut extract_info = FileIntl::default();
     let cm: Lrc<SourceMap> = Default::default();
-    let handler = Handler::with_tty_emitter(ColorConfig::Auto, true, false, Some(cm.clone()));
-    let fm = cm
-        .load_file(Path::new(path))
-        .expect(&(String::from("Failed to load ") + path));
+    let ext = get_extension(path);
+    let mut syntax = get_syntax(ext, options);
+    let mut directives = vec![];
+    let loaded = if ext == "vue" {
+        fs::read_to_string(path).map(|src| {
+            let sfc = parse_sfc(&src);
+            // the script `lang` decides the syntax, plain `<script>` is javascript
+            syntax = get_syntax(sfc.lang.as_deref().unwrap_or("js"), options);
+            directives = sfc.directives;
+            cm.new_source_file(Lrc::new(FileName::Real(path.to_path_buf())), sfc.source)
+        })
+    } else {
+        cm.load_file(path)
+    };
+    let fm = match loaded {
+        Ok(fm) => fm,
+        Err(e) => {
+            // binary or non utf-8 files matched by the include globs
+            extract_info.diagnostics.push(Diagnostic::new(
+                DiagnosticKind::ParseError,
+                format!("Failed to load file: {}.", e),
+                path.to_path_buf(),
+            ));
+            return extract_info;
+        }
+    };
     let lexer = Lexer::new(
         // We want to parse ecmascript
-        Syntax::Typescript(TsSyntax {
-            tsx: true,
-            decorators: true,
-            dts: false,
-            no_early_errors: false,
-            disallow_ambiguous_jsx_like: false,
-        }),
+        syntax,
         // EsVersion defaults to es5
         Default::default(),
         StringInput::from(&*fm),
@@ -273,21 +638,39 @@ pub fn extract_text<'a>(
 
     let mut parser = Parser::new_from(lexer);
 
-    for e in parser.take_errors() {
-        e.into_diagnostic(&handler).emit();
+    let parsed = parser.parse_module();
+    let mut errors = parser.take_errors();
+    // Unrecoverable fatal error occurred
+    let fatal = parsed.as_ref().err().cloned();
+    errors.extend(fatal);
+    for e in errors {
+        extract_info.diagnostics.push(Diagnostic::with_span(
+            &cm,
+            DiagnosticKind::ParseError,
+            format!("{}.", e.kind().msg()),
+            None,
+            e.span(),
+        ));
     }
-    let _module = parser
-        .parse_module()
-        .map_err(|e| {
-            // Unrecoverable fatal error occurred
-            e.into_diagnostic(&handler).emit()
-        })
-        .expect(&(String::from("Failed to parser module") + path));
+    let Ok(_module) = parsed else {
+        return extract_info;
+    };
 
     let mut visitor: TransformVisitor<'_> = TransformVisitor {
-        existed_map: existed_map,
-        visited_intl: extract_info,
+        visited_intl: &mut extract_info,
         cm: &cm,
+        options,
     };
     _module.visit_with(&mut visitor);
+
+    // vue `v-t` directive values are kept as expression statements at their original offsets
+    for item in &_module.body {
+        if let Some(stmt) = item.as_stmt().and_then(|stmt| stmt.as_expr()) {
+            let lo = (stmt.span.lo - fm.start_pos).0 as usize;
+            if directives.iter().any(|range| range.contains(&lo)) {
+                visitor.visit_vue_directive(&stmt.expr);
+            }
+        }
+    }
+    extract_info
 }
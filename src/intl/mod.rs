@@ -1,2 +1,8 @@
-pub mod run;
+pub mod cache;
+pub mod diagnostic;
 pub mod extract;
+pub mod render;
+pub mod report;
+pub mod run;
+pub mod vue;
+pub mod watch;
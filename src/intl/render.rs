@@ -0,0 +1,136 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use swc_common::errors::{ColorConfig, DiagnosticId, Handler};
+use swc_common::sync::Lrc;
+use swc_common::{BytePos, SourceFile, SourceMap, Span};
+
+use super::diagnostic::{Diagnostic, Severity};
+use super::extract::IntlInfo;
+
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn to_config(self) -> ColorConfig {
+        match self {
+            ColorChoice::Auto => ColorConfig::Auto,
+            ColorChoice::Always => ColorConfig::Always,
+            ColorChoice::Never => ColorConfig::Never,
+        }
+    }
+
+    fn is_enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => io::stderr().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+// One line per diagnostic, like `rustc --error-format=short`
+fn emit_compact(diagnostic: &Diagnostic, color: ColorChoice) {
+    if !color.is_enabled() {
+        eprintln!("{}", diagnostic);
+        return;
+    }
+    let severity = match diagnostic.severity {
+        Severity::Error => "\x1b[1;31merror",
+        Severity::Warning => "\x1b[1;33mwarning",
+    };
+    eprintln!(
+        "\x1b[1m{}:\x1b[0m {}[{}]\x1b[0m\x1b[1m: {}\x1b[0m",
+        diagnostic.location(),
+        severity,
+        diagnostic.kind.id(),
+        diagnostic.message
+    );
+}
+
+struct CodeFrameEmitter {
+    cm: Lrc<SourceMap>,
+    handler: Handler,
+    // files are loaded once, `None` when they can not be read anymore
+    files: HashMap<PathBuf, Option<Lrc<SourceFile>>>,
+}
+
+impl CodeFrameEmitter {
+    fn new(color: ColorChoice) -> CodeFrameEmitter {
+        let cm: Lrc<SourceMap> = Default::default();
+        let handler = Handler::with_tty_emitter(color.to_config(), true, false, Some(cm.clone()));
+        CodeFrameEmitter {
+            cm,
+            handler,
+            files: HashMap::new(),
+        }
+    }
+
+    // Spans of vue files point into the `.vue` file itself, so every file is loaded as is
+    fn get_span(&mut self, diagnostic: &Diagnostic) -> Option<Span> {
+        let range = diagnostic.range?;
+        let fm = self
+            .files
+            .entry(diagnostic.file.clone())
+            .or_insert_with(|| self.cm.load_file(&diagnostic.file).ok())
+            .as_ref()?;
+        if range.end.offset > fm.src.len() {
+            return None;
+        }
+        Some(Span::new(
+            fm.start_pos + BytePos(range.start.offset as u32),
+            fm.start_pos + BytePos(range.end.offset as u32),
+        ))
+    }
+
+    fn emit(&mut self, diagnostic: &Diagnostic) {
+        let code = DiagnosticId::Error(diagnostic.kind.id().to_string());
+        let span = self.get_span(diagnostic);
+        let mut builder = match (diagnostic.severity, span) {
+            (Severity::Error, Some(span)) => {
+                self.handler
+                    .struct_span_err_with_code(span, &diagnostic.message, code)
+            }
+            (Severity::Warning, Some(span)) => {
+                self.handler
+                    .struct_span_warn_with_code(span, &diagnostic.message, code)
+            }
+            (Severity::Error, None) => self.handler.struct_err_with_code(&diagnostic.message, code),
+            // there is no `struct_warn_with_code`
+            (Severity::Warning, None) => {
+                let mut builder = self.handler.struct_warn(&diagnostic.message);
+                builder.code(code);
+                builder
+            }
+        };
+        match span {
+            Some(span) => {
+                builder.span_label(span, diagnostic.kind.label());
+            }
+            None => {
+                builder.note(&format!("in {}", diagnostic.location()));
+            }
+        }
+        if let Some(help) = diagnostic.kind.help() {
+            builder.help(help);
+        }
+        builder.emit();
+    }
+}
+
+pub fn emit_diagnostics(intl_map: &IntlInfo, color: ColorChoice, compact: bool) {
+    let diagnostics = intl_map.diagnostics.values().flatten();
+    if compact {
+        diagnostics.for_each(|d| emit_compact(d, color));
+    } else {
+        let mut emitter = CodeFrameEmitter::new(color);
+        diagnostics.for_each(|d| emitter.emit(d));
+    }
+}
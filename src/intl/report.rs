@@ -0,0 +1,134 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::{json, to_writer_pretty, Value};
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+
+use super::diagnostic::{Diagnostic, DiagnosticKind};
+use super::extract::IntlInfo;
+
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    /// plain json with every problem found
+    Json,
+    /// SARIF 2.1.0, e.g. for GitHub code scanning
+    Sarif,
+}
+
+// Paths in reports are relative to `root` when possible, so they are stable across machines
+fn get_relative_path(file: &Path, root: &Path) -> String {
+    file.strip_prefix(root)
+        .unwrap_or(file)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn to_json(intl_map: &IntlInfo, root: &Path) -> Value {
+    let diagnostics: Vec<Value> = intl_map
+        .diagnostics
+        .values()
+        .flatten()
+        .map(|d: &Diagnostic| {
+            json!({
+                "kind": d.kind.id(),
+                "severity": d.severity.to_string(),
+                "message": d.message,
+                "key": d.key,
+                "file": get_relative_path(&d.file, root),
+                "line": d.range.map(|r| r.start.line),
+                "column": d.range.map(|r| r.start.column),
+                "endLine": d.range.map(|r| r.end.line),
+                "endColumn": d.range.map(|r| r.end.column),
+                "snippet": d.snippet,
+            })
+        })
+        .collect();
+    json!({
+        "extracted": intl_map.info_map.len(),
+        "diagnostics": diagnostics,
+    })
+}
+
+fn to_sarif(intl_map: &IntlInfo, root: &Path) -> Value {
+    let rules: Vec<Value> = DiagnosticKind::ALL
+        .iter()
+        .map(|kind| {
+            json!({
+                "id": kind.id(),
+                "shortDescription": { "text": kind.title() },
+                "defaultConfiguration": { "level": kind.severity().to_string() },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = intl_map
+        .diagnostics
+        .values()
+        .flatten()
+        .map(|d: &Diagnostic| {
+            let mut physical_location = json!({
+                "artifactLocation": {
+                    "uri": get_relative_path(&d.file, root),
+                    "uriBaseId": "%SRCROOT%",
+                },
+            });
+            if let Some(range) = &d.range {
+                physical_location["region"] = json!({
+                    "startLine": range.start.line,
+                    "startColumn": range.start.column,
+                    "endLine": range.end.line,
+                    "endColumn": range.end.column,
+                });
+                if let Some(snippet) = &d.snippet {
+                    physical_location["region"]["snippet"] = json!({ "text": snippet });
+                }
+            }
+            let rule_index = DiagnosticKind::ALL.iter().position(|kind| *kind == d.kind);
+            let mut result = json!({
+                "ruleId": d.kind.id(),
+                "ruleIndex": rule_index,
+                "level": d.severity.to_string(),
+                "message": { "text": d.message },
+                "locations": [{ "physicalLocation": physical_location }],
+            });
+            if let Some(key) = &d.key {
+                result["properties"] = json!({ "key": key });
+            }
+            result
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://crates.io/crates/intl-cli",
+                    "rules": rules,
+                },
+            },
+            "columnKind": "utf16CodeUnits",
+            "originalUriBaseIds": {
+                "%SRCROOT%": { "uri": format!("file://{}/", root.to_string_lossy().replace('\\', "/")) },
+            },
+            "results": results,
+        }],
+    })
+}
+
+pub fn write_report(
+    path: &Path,
+    format: ReportFormat,
+    intl_map: &IntlInfo,
+    root: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let report = match format {
+        ReportFormat::Json => to_json(intl_map, root),
+        ReportFormat::Sarif => to_sarif(intl_map, root),
+    };
+    to_writer_pretty(File::create(path)?, &report)?;
+    Ok(())
+}
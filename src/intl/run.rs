@@ -1,113 +1,361 @@
-use crate::intl::extract::extract_text;
+use crate::intl::extract::{extract_text, ExtractOptions};
+use crate::utils::hash::get_hash;
 use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use serde_json::Value;
 use serde_json::{from_reader, to_writer_pretty, Map};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
 use std::fs::{self, File};
-use std::path::Path;
-use std::{env, io};
+use std::io;
+use std::path::{Component, Path, PathBuf};
 
-use super::extract::IntlInfo;
+use super::cache::ExtractCache;
+use super::extract::{FileIntl, IntlInfo};
 
+pub struct RunOptions {
+    // directories to extract from, relative to the current directory
+    pub roots: Option<Vec<String>>,
+    // also extract files ignored by `.gitignore` and `.ignore`
+    pub no_ignore: bool,
+    pub output: Option<String>,
+    pub excludes: Option<Vec<String>>,
+    pub includes: Option<Vec<String>>,
+    pub delete_unreached: bool,
+    // number of files parsed in parallel, `None` uses one thread per CPU core
+    pub jobs: Option<usize>,
+    // cache file path, `None` parses every file
+    pub cache: Option<String>,
+}
+
+fn is_loop_err(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => is_loop_err(err),
+        _ => false,
+    }
+}
+
+// Files removed while the roots are walked, e.g. temporary files of editors
+fn is_not_found_err(err: &ignore::Error) -> bool {
+    err.io_error()
+        .is_some_and(|e| e.kind() == io::ErrorKind::NotFound)
+}
+
+// Include and exclude globs, matched against paths relative to the root a file is found in
+#[derive(Clone)]
+struct FileFilter {
+    includes: GlobSet,
+    excludes: GlobSet,
+    // directories whose files are all excluded, e.g. `**/node_modules` for `**/node_modules/**`
+    excluded_dirs: GlobSet,
+}
+
+impl FileFilter {
+    fn new(
+        includes: &Option<Vec<String>>,
+        excludes: &Option<Vec<String>>,
+    ) -> Result<FileFilter, Box<dyn Error>> {
+        let excluded_dirs: Option<Vec<String>> = excludes.as_ref().map(|excludes| {
+            excludes
+                .iter()
+                .filter_map(|pattern| pattern.strip_suffix("/**"))
+                .map(|pattern| pattern.to_string())
+                .collect()
+        });
+        Ok(FileFilter {
+            includes: build_glob_set(includes)?,
+            excludes: build_glob_set(excludes)?,
+            excluded_dirs: build_glob_set(&excluded_dirs)?,
+        })
+    }
+
+    fn is_match(&self, relative_path: &Path) -> bool {
+        self.includes.is_match(relative_path) && !self.excludes.is_match(relative_path)
+    }
+
+    // Excluded directories are not entered at all
+    fn is_excluded_dir(&self, relative_path: &Path) -> bool {
+        self.excluded_dirs.is_match(relative_path) || self.excludes.is_match(relative_path)
+    }
+}
+
+// Walk the roots the way git sees them: files ignored by `.gitignore` and `.ignore` are
+// skipped, also outside of a git repository. Symlinks are followed, links back to one of
+// their parent directories are skipped.
 fn visit_dirs(
-    dir: &Path,
-    includes: &GlobSet,
-    excludes: &GlobSet,
-    existed_map: &Map<String, Value>,
-    intl_map: &mut IntlInfo,
-) -> io::Result<()> {
-    if dir.is_dir() {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
+    roots: &[PathBuf],
+    filter: &FileFilter,
+    no_ignore: bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), ignore::Error> {
+    for root in roots {
+        let dir_filter = filter.clone();
+        let dir_root = root.clone();
+        let walker = WalkBuilder::new(root)
+            .standard_filters(!no_ignore)
+            .hidden(false)
+            .require_git(false)
+            .follow_links(true)
+            .filter_entry(move |entry| {
+                let is_dir = entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_dir());
+                !(is_dir
+                    && entry.depth() > 0
+                    && entry
+                        .path()
+                        .strip_prefix(&dir_root)
+                        .is_ok_and(|path| dir_filter.is_excluded_dir(path)))
+            })
+            .build();
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) if is_loop_err(&err) || is_not_found_err(&err) => continue,
+                Err(err) => return Err(err),
+            };
             let path = entry.path();
-            if path.is_dir() {
-                visit_dirs(&path, includes, excludes, existed_map, intl_map)?;
-            } else {
-                if includes.is_match(entry.path()) && !excludes.is_match(entry.path()) {
-                    extract_text(entry.path().to_str().unwrap(), existed_map, intl_map);
-                }
+            if entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+                && path
+                    .strip_prefix(root)
+                    .is_ok_and(|relative_path| filter.is_match(relative_path))
+            {
+                files.push(path.to_path_buf());
             }
         }
     }
+    // roots may overlap
+    files.sort();
+    files.dedup();
     Ok(())
 }
 
-pub fn run_extract(
-    output: Option<String>,
-    excludes: Option<Vec<String>>,
-    includes: Option<Vec<String>>,
-    delete_unreached: bool,
-) -> () {
-    let mut includes_builder = GlobSetBuilder::new();
-    let mut excludes_builder = GlobSetBuilder::new();
-
-    if let Some(includes) = includes {
-        for includes_path in includes {
-            includes_builder.add(
-                Glob::new(includes_path.as_str())
-                    .expect("Failed to read includes glob pattern, please check includes path"),
-            );
+// `packages/web/./src` => `<current dir>/packages/web/src`
+fn get_root(current_dir: &Path, root: &str) -> PathBuf {
+    current_dir
+        .join(root)
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+fn build_glob_set(patterns: &Option<Vec<String>>) -> Result<GlobSet, Box<dyn Error>> {
+    let mut builder = GlobSetBuilder::new();
+    if let Some(patterns) = patterns {
+        for pattern in patterns {
+            builder.add(Glob::new(pattern.as_str())?);
         }
     }
-    if let Some(excludes) = excludes {
-        for excludes_path in excludes {
-            excludes_builder.add(
-                Glob::new(excludes_path.as_str())
-                    .expect("Failed to read excludes glob pattern, please check excludes path"),
-            );
+    Ok(builder.build()?)
+}
+
+// Extraction state shared by a single run and watch mode
+pub struct Extractor<'a> {
+    pub roots: Vec<PathBuf>,
+    no_ignore: bool,
+    filter: FileFilter,
+    output_path: Option<PathBuf>,
+    delete_unreached: bool,
+    options: &'a ExtractOptions,
+    pool: ThreadPool,
+    cache_path: Option<PathBuf>,
+    cache: Option<ExtractCache>,
+    // (content hash, result) of every matched file, sorted by path so the merge order is stable
+    files: BTreeMap<PathBuf, (Option<String>, FileIntl)>,
+}
+
+impl Extractor<'_> {
+    pub fn new(
+        run_options: RunOptions,
+        options: &ExtractOptions,
+    ) -> Result<Extractor<'_>, Box<dyn Error>> {
+        let current_dir = env::current_dir()?;
+        let roots = match &run_options.roots {
+            Some(roots) => roots
+                .iter()
+                .map(|root| get_root(&current_dir, root))
+                .collect(),
+            None => vec![current_dir.clone()],
+        };
+        // 0 threads lets rayon use one per CPU core
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(run_options.jobs.unwrap_or(0))
+            .build()?;
+        let cache_path = run_options.cache.map(|cache| current_dir.join(cache));
+        let cache = cache_path
+            .as_ref()
+            .map(|path| ExtractCache::load(path, options));
+        Ok(Extractor {
+            filter: FileFilter::new(&run_options.includes, &run_options.excludes)?,
+            output_path: run_options.output.map(|output| current_dir.join(output)),
+            delete_unreached: run_options.delete_unreached,
+            options,
+            pool,
+            cache_path,
+            cache,
+            files: BTreeMap::new(),
+            roots,
+            no_ignore: run_options.no_ignore,
+        })
+    }
+
+    // `path` is matched relative to every root it is in
+    pub fn is_match(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| {
+            path.strip_prefix(root)
+                .is_ok_and(|relative_path| self.filter.is_match(relative_path))
+        })
+    }
+
+    pub fn is_known(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    pub fn collect_files(&self) -> Result<Vec<PathBuf>, ignore::Error> {
+        let mut files = vec![];
+        visit_dirs(&self.roots, &self.filter, self.no_ignore, &mut files)?;
+        Ok(files)
+    }
+
+    // extracted files in `dir`, or `dir` itself when it is an extracted file
+    pub fn get_files_in(&self, dir: &Path) -> Vec<PathBuf> {
+        self.files
+            .keys()
+            .filter(|path| path.starts_with(dir))
+            .cloned()
+            .collect()
+    }
+
+    // Extract the given files in parallel, files that no longer exist are forgotten.
+    // Returns whether any result changed.
+    pub fn extract(&mut self, paths: Vec<PathBuf>) -> bool {
+        let mut changed = false;
+        let (existed, removed): (Vec<_>, Vec<_>) =
+            paths.into_iter().partition(|path| path.is_file());
+        for path in removed {
+            changed |= self.files.remove(&path).is_some();
         }
+        let files = &self.files;
+        let cache = &self.cache;
+        let options = self.options;
+        let file_intl_list: Vec<_> = self.pool.install(|| {
+            existed
+                .into_par_iter()
+                .filter_map(|path| {
+                    // files that can not be read are not cached, `extract_text` reports them
+                    let hash = fs::read(&path).ok().map(|content| get_hash(&content));
+                    // watchers also report files that were only read, skip unchanged content
+                    if hash.is_some() && files.get(&path).is_some_and(|(h, _)| *h == hash) {
+                        return None;
+                    }
+                    let cached = match (cache, &hash) {
+                        (Some(cache), Some(hash)) => cache.get(hash, &path),
+                        _ => None,
+                    };
+                    let file_intl = cached.unwrap_or_else(|| extract_text(&path, options));
+                    Some((path, (hash, file_intl)))
+                })
+                .collect()
+        });
+        changed |= !file_intl_list.is_empty();
+        self.files.extend(file_intl_list);
+        changed
     }
-    let includes_set = includes_builder
-        .build()
-        .expect("Failed to build includes glob set");
-    let excludes_set = excludes_builder
-        .build()
-        .expect("Failed to build excludes glob set");
-    let current_dir = env::current_dir().expect("Failed to get current directory");
-    let mut existed_map: Map<String, Value> = Map::new();
-    let output_path = Path::new(current_dir.to_str().unwrap()).join(output.clone().unwrap());
-    if let Ok(file) = File::open(output_path) {
-        if let Ok(json_str) = from_reader::<File, Value>(file) {
-            let json_obj = json_str.as_object();
-            if let Some(obj) = json_obj {
-                existed_map = obj.to_owned();
+
+    // A cache that can not be written only makes the next run slower, the output is still written
+    pub fn save_cache(&mut self) {
+        if let (Some(path), Some(cache)) = (&self.cache_path, &mut self.cache) {
+            cache.update(self.files.iter());
+            if let Err(e) = cache.save(path) {
+                eprintln!("Warning: Failed to write cache {}: {}", path.display(), e);
             }
         }
     }
 
-    let mut intl_map = IntlInfo {
-        info_map: HashMap::new(),
-        err_map: HashMap::new(),
-        repeat_key_list: Vec::new(),
-    };
-
-    visit_dirs(
-        &current_dir,
-        &includes_set,
-        &excludes_set,
-        &existed_map,
-        &mut intl_map,
-    )
-    .expect("Failed to visit directory");
-
-    println!("{}", intl_map);
-    if delete_unreached {
-        existed_map.clear();
-        for (key, default) in intl_map.repeat_key_list {
-            existed_map.insert(key, default);
+    // Merge every file into the existing output. The output is only rewritten when it changed,
+    // unless `always_write` is set.
+    pub fn merge(&self, always_write: bool) -> Result<IntlInfo, Box<dyn Error>> {
+        let mut existed_map: Map<String, Value> = Map::new();
+        if let Some(output_path) = &self.output_path {
+            // a missing output file is created, an unparsable one is not overwritten
+            match File::open(output_path) {
+                Ok(file) => {
+                    let json_str = from_reader::<File, Value>(file).map_err(|e| {
+                        format!("Failed to parse output file {:?}: {}", output_path, e)
+                    })?;
+                    existed_map = match json_str {
+                        Value::Object(obj) => obj,
+                        _ => {
+                            return Err(format!(
+                                "Output file {:?} is not a JSON object",
+                                output_path
+                            )
+                            .into())
+                        }
+                    };
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => {
+                    return Err(
+                        format!("Failed to open output file {:?}: {}", output_path, e).into(),
+                    )
+                }
+            }
         }
-    }
 
-    for (_, value) in intl_map.info_map.iter() {
-        existed_map.insert(
-            value.key.to_string(),
-            Value::String(value.default.to_string()),
-        );
-    }
-    if let Some(s) = output {
-        let output = Path::new(current_dir.to_str().unwrap()).join(s);
-        if let Ok(file) = File::create(output) {
-            to_writer_pretty(file, &existed_map).unwrap();
+        // cached files are merged too, so the key set is complete for `delete_unreached`
+        let mut intl_map = IntlInfo::default();
+        for (_, file_intl) in self.files.values() {
+            intl_map.merge(file_intl, &existed_map);
+        }
+
+        let mut output_map = existed_map.clone();
+        if self.delete_unreached {
+            output_map.clear();
+            for (key, default) in intl_map.repeat_key_list.iter() {
+                output_map.insert(key.to_string(), default.clone());
+            }
+        }
+
+        for (_, value) in intl_map.info_map.iter() {
+            output_map.insert(
+                value.key.to_string(),
+                Value::String(value.default.to_string()),
+            );
         }
+        if let Some(output_path) = &self.output_path {
+            if always_write || output_map != existed_map {
+                to_writer_pretty(File::create(output_path)?, &output_map)?;
+            }
+        }
+        Ok(intl_map)
     }
 }
+
+pub fn run_extract(
+    run_options: RunOptions,
+    options: ExtractOptions,
+) -> Result<IntlInfo, Box<dyn Error>> {
+    let mut extractor = Extractor::new(run_options, &options)?;
+    let files = extractor.collect_files()?;
+    extractor.extract(files);
+    extractor.save_cache();
+    extractor.merge(true)
+}
+
+// Files `run_extract` would extract, without parsing them
+pub fn list_extract_files(
+    run_options: RunOptions,
+    options: ExtractOptions,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let extractor = Extractor::new(run_options, &options)?;
+    Ok(extractor.collect_files()?)
+}
@@ -0,0 +1,366 @@
+use std::ops::Range;
+
+// A Vue single-file component turned into a script the ecmascript parser understands.
+//
+// Script blocks and template expressions are kept at their original byte offsets, everything
+// else is blanked out with whitespace of the same byte length, so spans and line/column numbers
+// reported by the parser point into the `.vue` file itself.
+pub struct VueSfc {
+    pub source: String,
+    // `lang` attribute of the script blocks, e.g. "ts"
+    pub lang: Option<String>,
+    // byte ranges of `v-t` directive values
+    pub directives: Vec<Range<usize>>,
+}
+
+struct Blanker {
+    keep: Vec<Range<usize>>,
+    // single ascii characters replaced to separate the kept expressions, e.g. `;(` and `)`
+    replace: Vec<(usize, char)>,
+}
+
+impl Blanker {
+    // Keep `value` as a standalone expression statement. `semi` and `open` are the
+    // positions of two ascii characters before it, `close` is the one right after it.
+    fn expr(&mut self, value: Range<usize>, semi: usize, open: usize, close: usize) {
+        self.replace.push((semi, ';'));
+        self.replace.push((open, '('));
+        self.replace.push((close, ')'));
+        self.keep.push(value);
+    }
+
+    // Keep `value` as statements, e.g. `@click="count++; emit('done')"`
+    fn stmt(&mut self, value: Range<usize>, semi: usize) {
+        self.replace.push((semi, ';'));
+        self.keep.push(value);
+    }
+
+    fn build(mut self, src: &str) -> String {
+        self.keep.sort_by_key(|r| r.start);
+        self.replace.sort_by_key(|r| r.0);
+        let mut keep = self.keep.iter().peekable();
+        let mut replace = self.replace.iter().peekable();
+        let mut source = String::with_capacity(src.len());
+        for (idx, c) in src.char_indices() {
+            while keep.peek().is_some_and(|r| r.end <= idx) {
+                keep.next();
+            }
+            while replace.peek().is_some_and(|r| r.0 < idx) {
+                replace.next();
+            }
+            if keep.peek().is_some_and(|r| r.contains(&idx)) {
+                source.push(c);
+            } else if let Some((_, r)) = replace.peek().filter(|r| r.0 == idx) {
+                source.push(*r);
+            } else if c == '\n' || c == '\r' {
+                source.push(c);
+            } else {
+                // whitespace with the same utf-8 length keeps the byte offsets. Columns only stay
+                // the same because swc counts them in utf-16 code units, which a 4 byte char has
+                // two of, like the two NBSPs replacing it.
+                match c.len_utf8() {
+                    1 => source.push(' '),
+                    2 => source.push('\u{a0}'),
+                    3 => source.push('\u{3000}'),
+                    _ => source.push_str("\u{a0}\u{a0}"),
+                }
+            }
+        }
+        source
+    }
+}
+
+fn find(src: &str, pat: &str, from: usize) -> Option<usize> {
+    src.get(from..)?.find(pat).map(|idx| idx + from)
+}
+
+fn is_tag_start(src: &str, idx: usize, name: &str) -> bool {
+    src[idx..].starts_with(name)
+        && src[idx + name.len()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_whitespace() || c == '>' || c == '/')
+}
+
+struct Attr {
+    name: Range<usize>,
+    eq: usize,
+    // quoted value without the quotes
+    value: Range<usize>,
+}
+
+// Parse the attributes of a tag starting after its name, returns them and the offset after `>`
+fn parse_attrs(src: &str, mut idx: usize) -> (Vec<Attr>, usize) {
+    let bytes = src.as_bytes();
+    let mut attrs = vec![];
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'>' => return (attrs, idx + 1),
+            b'/' => idx += 1,
+            c if c.is_ascii_whitespace() => idx += 1,
+            _ => {
+                let name_start = idx;
+                while idx < bytes.len()
+                    && !bytes[idx].is_ascii_whitespace()
+                    && !matches!(bytes[idx], b'=' | b'>' | b'/')
+                {
+                    idx += 1;
+                }
+                let name = name_start..idx;
+                while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+                    idx += 1;
+                }
+                if idx >= bytes.len() || bytes[idx] != b'=' {
+                    continue;
+                }
+                let eq = idx;
+                idx += 1;
+                while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+                    idx += 1;
+                }
+                if idx < bytes.len() && matches!(bytes[idx], b'"' | b'\'') {
+                    let quote = bytes[idx] as char;
+                    let value_start = idx + 1;
+                    let value_end = find(src, &quote.to_string(), value_start).unwrap_or(src.len());
+                    attrs.push(Attr {
+                        name,
+                        eq,
+                        value: value_start..value_end,
+                    });
+                    idx = value_end + 1;
+                } else {
+                    // unquoted values are never treated as expressions
+                    while idx < bytes.len()
+                        && !bytes[idx].is_ascii_whitespace()
+                        && bytes[idx] != b'>'
+                    {
+                        idx += 1;
+                    }
+                }
+            }
+        }
+    }
+    (attrs, bytes.len())
+}
+
+fn get_attr<'a>(src: &'a str, attrs: &[Attr], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|attr| &src[attr.name.clone()] == name)
+        .map(|attr| &src[attr.value.clone()])
+}
+
+fn scan_template(
+    src: &str,
+    range: Range<usize>,
+    blanker: &mut Blanker,
+    directives: &mut Vec<Range<usize>>,
+) {
+    let bytes = src.as_bytes();
+    let mut idx = range.start;
+    while idx < range.end {
+        if src[idx..].starts_with("<!--") {
+            idx = find(src, "-->", idx).map_or(range.end, |end| end + 3);
+        } else if src[idx..].starts_with("{{") {
+            let Some(end) = find(src, "}}", idx + 2).filter(|end| *end <= range.end) else {
+                break;
+            };
+            // `{{ $t('key') }}`
+            blanker.expr(idx + 2..end, idx, idx + 1, end);
+            idx = end + 2;
+        } else if bytes[idx] == b'<' && bytes.get(idx + 1).is_some_and(|c| c.is_ascii_alphabetic())
+        {
+            let mut name_end = idx + 1;
+            while name_end < bytes.len()
+                && !bytes[name_end].is_ascii_whitespace()
+                && !matches!(bytes[name_end], b'>' | b'/')
+            {
+                name_end += 1;
+            }
+            let (attrs, end) = parse_attrs(src, name_end);
+            for attr in attrs {
+                let name = &src[attr.name.clone()];
+                let (open, close) = (attr.value.start - 1, attr.value.end);
+                if name == "v-t" {
+                    // `v-t="'key'"` and `v-t="{ path: 'key' }"`
+                    directives.push(open..close + 1);
+                    blanker.expr(attr.value, attr.eq, open, close);
+                } else if name == "v-for" {
+                    // `v-for="item in $t('list')"`, only the iterated expression is valid script
+                    let value = &src[attr.value.clone()];
+                    if let Some(pos) = [" in ", " of "].iter().filter_map(|p| value.find(p)).min() {
+                        let start = attr.value.start + pos + 4;
+                        blanker.expr(start..attr.value.end, attr.eq, start - 1, close);
+                    }
+                } else if name == "v-slot" || name.starts_with("v-slot:") || name.starts_with('#') {
+                    // slot props are patterns, not expressions
+                } else if name.starts_with('@') || name.starts_with("v-on") {
+                    blanker.stmt(attr.value, attr.eq);
+                } else if name.starts_with(':') || name.starts_with("v-") {
+                    blanker.expr(attr.value, attr.eq, open, close);
+                }
+            }
+            idx = end;
+        } else {
+            idx += src[idx..].chars().next().map_or(1, |c| c.len_utf8());
+        }
+    }
+}
+
+// Find the closing tag of a top level block, counting nested tags with the same name
+fn find_close_tag(src: &str, name: &str, mut idx: usize) -> Option<usize> {
+    let open = format!("<{}", name);
+    let close = format!("</{}", name);
+    let mut depth = 1;
+    loop {
+        let next_close = find(src, &close, idx)?;
+        match find(src, &open, idx).filter(|next_open| *next_open < next_close) {
+            Some(next_open) if is_tag_start(src, next_open, &open) => {
+                depth += 1;
+                idx = next_open + open.len();
+            }
+            Some(next_open) => idx = next_open + open.len(),
+            None => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(next_close);
+                }
+                idx = next_close + close.len();
+            }
+        }
+    }
+}
+
+pub fn parse_sfc(src: &str) -> VueSfc {
+    let mut blanker = Blanker {
+        keep: vec![],
+        replace: vec![],
+    };
+    let mut directives = vec![];
+    let mut lang = None;
+    let bytes = src.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if src[idx..].starts_with("<!--") {
+            idx = find(src, "-->", idx).map_or(bytes.len(), |end| end + 3);
+        } else if bytes[idx] == b'<' && bytes.get(idx + 1).is_some_and(|c| c.is_ascii_alphabetic())
+        {
+            let mut name_end = idx + 1;
+            while name_end < bytes.len()
+                && !bytes[name_end].is_ascii_whitespace()
+                && !matches!(bytes[name_end], b'>' | b'/')
+            {
+                name_end += 1;
+            }
+            let name = &src[idx + 1..name_end];
+            let (attrs, content_start) = parse_attrs(src, name_end);
+            if src[..content_start].ends_with("/>") {
+                idx = content_start;
+                continue;
+            }
+            let content_end = find_close_tag(src, name, content_start).unwrap_or(bytes.len());
+            match name {
+                "script" => {
+                    if let Some(l) = get_attr(src, &attrs, "lang") {
+                        lang = Some(l.to_string());
+                    }
+                    blanker.keep.push(content_start..content_end);
+                }
+                // templates written in pug and other languages are not scanned
+                "template" if get_attr(src, &attrs, "lang").is_none_or(|l| l == "html") => {
+                    scan_template(
+                        src,
+                        content_start..content_end,
+                        &mut blanker,
+                        &mut directives,
+                    );
+                }
+                _ => (),
+            }
+            idx = find(src, ">", content_end).map_or(bytes.len(), |end| end + 1);
+        } else {
+            idx += src[idx..].chars().next().map_or(1, |c| c.len_utf8());
+        }
+    }
+    VueSfc {
+        source: blanker.build(src),
+        lang,
+        directives,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The kept script with every run of blanked out whitespace collapsed
+    fn get_script(sfc: &VueSfc) -> String {
+        sfc.source.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    fn parse(src: &str) -> VueSfc {
+        let sfc = parse_sfc(src);
+        assert_eq!(sfc.source.len(), src.len());
+        sfc
+    }
+
+    #[test]
+    fn keeps_scripts_and_mustaches() {
+        let src = "<template>\n  <p>{{ $t('a') }}</p>\n</template>\n<script setup lang=\"ts\">\n$t('b');\n</script>\n";
+        let sfc = parse(src);
+        assert_eq!(get_script(&sfc), ";( $t('a') ) $t('b');");
+        assert_eq!(sfc.source.find("$t('a')"), src.find("$t('a')"));
+        assert_eq!(sfc.source.lines().count(), src.lines().count());
+        assert_eq!(sfc.lang.as_deref(), Some("ts"));
+    }
+
+    #[test]
+    fn keeps_directive_values() {
+        let src = r#"<template><p v-t="'a'"></p><p v-t="{ path: 'b' }" :title="$t('c')" @click="n++; $t('d')"></p></template>"#;
+        let sfc = parse(src);
+        assert_eq!(
+            get_script(&sfc),
+            ";('a') ;({ path: 'b' }) ;($t('c')) ; n++; $t('d')"
+        );
+        let directives: Vec<_> = sfc.directives.iter().map(|r| &src[r.clone()]).collect();
+        assert_eq!(directives, [r#""'a'""#, r#""{ path: 'b' }""#]);
+    }
+
+    #[test]
+    fn keeps_only_the_iterated_expression_of_v_for() {
+        let src = r#"<template><li v-for="(item, i) in $t('list')">{{ item }}</li></template>"#;
+        assert_eq!(get_script(&parse(src)), "; ($t('list')) ;( item )");
+    }
+
+    #[test]
+    fn skips_slot_props() {
+        let src = r#"<template><Item #row="{ label }">{{ $t(label) }}</Item><Item v-slot:default="props" /></template>"#;
+        assert_eq!(get_script(&parse(src)), ";( $t(label) )");
+    }
+
+    #[test]
+    fn skips_comments() {
+        let src = "<!-- <script>$t('a')</script> -->\n<template><!-- {{ $t('b') }} --><p>{{ $t('c') }}</p></template>";
+        assert_eq!(get_script(&parse(src)), ";( $t('c') )");
+    }
+
+    #[test]
+    fn finds_the_close_tag_of_nested_templates() {
+        let src = r#"<template><div><template v-if="ok">{{ $t('a') }}</template></div>{{ $t('b') }}</template><script>$t('c')</script>"#;
+        assert_eq!(
+            get_script(&parse(src)),
+            ";(ok) ;( $t('a') ) ;( $t('b') ) $t('c')"
+        );
+    }
+
+    #[test]
+    fn keeps_offsets_and_utf16_columns_after_multi_byte_text() {
+        let src = "<template><p>é你😀 {{ $t('a') }}</p></template>";
+        let sfc = parse(src);
+        assert_eq!(get_script(&sfc), ";( $t('a') )");
+        let idx = src.find("$t").unwrap();
+        assert_eq!(sfc.source.find("$t"), Some(idx));
+        let utf16_len = |s: &str| s.encode_utf16().count();
+        assert_eq!(utf16_len(&sfc.source[..idx]), utf16_len(&src[..idx]));
+    }
+}
@@ -0,0 +1,77 @@
+use notify_debouncer_mini::new_debouncer;
+use notify_debouncer_mini::notify::RecursiveMode;
+use std::collections::{BTreeSet, HashSet};
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+use super::extract::{ExtractOptions, IntlInfo};
+use super::run::{Extractor, RunOptions};
+
+// Editors often write a file in several steps, wait until they are done
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(200);
+
+// Extract every file once, then re-extract the files that change until the process is stopped.
+// `on_extract` is called after every extraction, the output is only rewritten when it changed.
+pub fn watch_extract(
+    run_options: RunOptions,
+    options: ExtractOptions,
+    mut on_extract: impl FnMut(&IntlInfo),
+) -> Result<(), Box<dyn Error>> {
+    let mut extractor = Extractor::new(run_options, &options)?;
+    let files = extractor.collect_files()?;
+    extractor.extract(files);
+    extractor.save_cache();
+    on_extract(&extractor.merge(true)?);
+
+    let (tx, rx) = channel();
+    let mut debouncer = new_debouncer(DEBOUNCE_TIMEOUT, tx)?;
+    for root in extractor.roots.iter() {
+        debouncer.watcher().watch(root, RecursiveMode::Recursive)?;
+    }
+
+    for result in rx {
+        let events = match result {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!("Error: Failed to watch files: {}", e);
+                continue;
+            }
+        };
+        let mut paths: BTreeSet<PathBuf> = BTreeSet::new();
+        let mut new_paths = vec![];
+        for event in events {
+            if !event.path.exists() {
+                // a removed directory only has an event of its own, forget the files in it
+                paths.extend(extractor.get_files_in(&event.path));
+            } else if extractor.is_known(&event.path) {
+                paths.insert(event.path);
+            } else if extractor.is_match(&event.path) {
+                new_paths.push(event.path);
+            }
+        }
+        // new files are only extracted when walking the roots finds them, so they are not
+        // ignored by `.gitignore` or `.ignore`
+        // errors while watching are reported and the next change is waited for, e.g. an output
+        // file that is locked for a moment
+        if !new_paths.is_empty() {
+            match extractor.collect_files() {
+                Ok(files) => {
+                    let files: HashSet<PathBuf> = files.into_iter().collect();
+                    paths.extend(new_paths.into_iter().filter(|path| files.contains(path)));
+                }
+                Err(e) => eprintln!("Error: Failed to find new files: {}", e),
+            }
+        }
+        if !extractor.extract(paths.into_iter().collect()) {
+            continue;
+        }
+        extractor.save_cache();
+        match extractor.merge(false) {
+            Ok(intl_map) => on_extract(&intl_map),
+            Err(e) => eprintln!("Error: Failed to write output: {}", e),
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,13 @@
 mod cli;
+mod config;
 mod intl;
 mod translate;
 mod utils;
 
+use std::process::ExitCode;
+
 use crate::cli::run_cli;
 
-fn main() {
-    run_cli();
+fn main() -> ExitCode {
+    run_cli()
 }
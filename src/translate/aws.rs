@@ -0,0 +1,323 @@
+use chrono::Utc;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+
+use crate::utils::hash::{get_hash, sha256};
+
+use super::credential::AwsCredential;
+use super::provider::{
+    get_billed_chars, get_text, parse_endpoint, BatchResult, BoxFuture, HttpError, LengthUnit,
+    ProviderLimits, TranslationProvider,
+};
+use super::retry::{is_retryable_http, is_retryable_status};
+
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+// `TranslateText` takes one text of at most 10000 bytes
+const MAX_BATCH_BYTES: usize = 10000;
+const MAX_BATCH_TEXTS: usize = 1;
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Request<'a> {
+    text: String,
+    source_language_code: &'a str,
+    target_language_code: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Response {
+    translated_text: String,
+}
+
+// The error of a request, e.g. `UnrecognizedClientException` for a wrong access key
+#[derive(Deserialize)]
+struct ErrorResponse {
+    #[serde(rename = "__type", default)]
+    kind: String,
+    #[serde(alias = "Message", default)]
+    message: String,
+}
+
+// Throttling is answered with 400
+const RETRYABLE_ERRORS: [&str; 4] = [
+    "ThrottlingException",
+    "TooManyRequestsException",
+    "ServiceUnavailableException",
+    "InternalServerException",
+];
+
+// The parts of a request covered by a Signature Version 4 signature
+struct SignedRequest<'a> {
+    method: &'a str,
+    path: &'a str,
+    query: &'a str,
+    // (lowercase name, value), sorted by name
+    headers: &'a [(&'a str, &'a str)],
+    payload: &'a [u8],
+}
+
+fn get_canonical_request(request: &SignedRequest) -> String {
+    let canonical_headers: String = request
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers: Vec<&str> = request.headers.iter().map(|(name, _)| *name).collect();
+    format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method,
+        request.path,
+        request.query,
+        canonical_headers,
+        signed_headers.join(";"),
+        get_hash(request.payload)
+    )
+}
+
+// `Authorization` header of a request signed with Signature Version 4, `amz_date` is the
+// `X-Amz-Date` header, e.g. `20150830T123600Z`
+fn get_authorization(
+    request: &SignedRequest,
+    credential: &AwsCredential,
+    region: &str,
+    service: &str,
+    amz_date: &str,
+) -> String {
+    // Step 1: Build canonical request
+    let canonical_request = get_canonical_request(request);
+
+    // Step 2: Build string to sign
+    let algorithm = "AWS4-HMAC-SHA256";
+    let date = &amz_date[..8];
+    let credential_scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        algorithm,
+        amz_date,
+        credential_scope,
+        get_hash(canonical_request.as_bytes())
+    );
+
+    // Step 3: Calculate signature
+    let k_date = sha256(
+        date.as_bytes(),
+        format!("AWS4{}", credential.secret_access_key.expose()).as_bytes(),
+    );
+    let k_region = sha256(region.as_bytes(), &k_date);
+    let k_service = sha256(service.as_bytes(), &k_region);
+    let k_signing = sha256(b"aws4_request", &k_service);
+    let signature = hex::encode(sha256(string_to_sign.as_bytes(), &k_signing));
+
+    // Step 4: Build Authorization
+    let signed_headers: Vec<&str> = request.headers.iter().map(|(name, _)| *name).collect();
+    format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        algorithm,
+        credential.access_key_id.expose(),
+        credential_scope,
+        signed_headers.join(";"),
+        signature
+    )
+}
+
+// Amazon Translate, texts are sent one by one with `TranslateText`
+pub struct AwsProvider {
+    credential: AwsCredential,
+    region: String,
+    url: Url,
+    // `Host` header, also part of the signature
+    host: String,
+    client: reqwest::Client,
+}
+
+impl AwsProvider {
+    // Without `endpoint` requests go to `translate.<region>.amazonaws.com`
+    pub fn new(
+        credential: AwsCredential,
+        region: String,
+        endpoint: Option<&str>,
+    ) -> Result<AwsProvider, Box<dyn Error>> {
+        let endpoint = match endpoint {
+            Some(endpoint) => endpoint.to_string(),
+            None => format!("https://translate.{}.amazonaws.com", region),
+        };
+        let (url, host) = parse_endpoint(&endpoint)?;
+        Ok(AwsProvider {
+            credential,
+            region,
+            url,
+            host,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    async fn generate_by_aws(
+        &self,
+        pair_list: &[(String, Value)],
+        source: &str,
+        target: &str,
+    ) -> Result<BatchResult, Box<dyn Error>> {
+        const SERVICE: &str = "translate";
+        const TARGET: &str = "AWSShineFrontendService_20170701.TranslateText";
+        const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+
+        let mut texts = Vec::with_capacity(pair_list.len());
+        for (_, value) in pair_list {
+            let credential = &self.credential;
+            let now = Utc::now();
+            let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+
+            let payload = serde_json::to_string(&Request {
+                text: get_text(value),
+                source_language_code: source,
+                target_language_code: target,
+            })?;
+
+            // headers sorted by name
+            let mut signed_headers = vec![
+                ("content-type", CONTENT_TYPE),
+                ("host", self.host.as_str()),
+                ("x-amz-date", amz_date.as_str()),
+            ];
+            if let Some(token) = &credential.session_token {
+                signed_headers.push(("x-amz-security-token", token.expose()));
+            }
+            signed_headers.push(("x-amz-target", TARGET));
+            let authorization = get_authorization(
+                &SignedRequest {
+                    method: "POST",
+                    path: self.url.path(),
+                    query: self.url.query().unwrap_or_default(),
+                    headers: &signed_headers,
+                    payload: payload.as_bytes(),
+                },
+                credential,
+                &self.region,
+                SERVICE,
+                &amz_date,
+            );
+
+            // Create and send request
+            let mut headers = HeaderMap::new();
+            headers.insert("Authorization", HeaderValue::from_str(&authorization)?);
+            headers.insert("Content-Type", HeaderValue::from_static(CONTENT_TYPE));
+            headers.insert("Host", HeaderValue::from_str(&self.host)?);
+            headers.insert("X-Amz-Date", HeaderValue::from_str(&amz_date)?);
+            headers.insert("X-Amz-Target", HeaderValue::from_static(TARGET));
+            if let Some(token) = &credential.session_token {
+                headers.insert(
+                    "X-Amz-Security-Token",
+                    HeaderValue::from_str(token.expose())?,
+                );
+            }
+
+            let response = self
+                .client
+                .post(self.url.clone())
+                .headers(headers)
+                .body(payload)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let e = HttpError::from_response(self.name(), response, |e: ErrorResponse| {
+                    // `__type` may have a namespace, e.g. `com.amazonaws.translate#...`
+                    let kind = e.kind.rsplit('#').next().unwrap_or_default();
+                    (Some(kind.to_string()).filter(|k| !k.is_empty()), e.message)
+                });
+                return Err(e.await.into());
+            }
+            texts.push(Some(response.json::<Response>().await?.translated_text));
+        }
+        Ok(BatchResult {
+            texts,
+            used_amount: get_billed_chars(pair_list),
+        })
+    }
+}
+
+impl TranslationProvider for AwsProvider {
+    fn name(&self) -> &str {
+        "AWS Translate"
+    }
+
+    fn limits(&self) -> ProviderLimits {
+        ProviderLimits {
+            max_len: MAX_BATCH_BYTES,
+            unit: LengthUnit::Bytes,
+            max_texts: MAX_BATCH_TEXTS,
+        }
+    }
+
+    fn is_retryable(&self, err: &(dyn Error + 'static)) -> bool {
+        match err.downcast_ref::<HttpError>() {
+            Some(e) => {
+                e.code
+                    .as_deref()
+                    .is_some_and(|code| RETRYABLE_ERRORS.contains(&code))
+                    || is_retryable_status(e.status)
+            }
+            None => is_retryable_http(err),
+        }
+    }
+
+    fn translate<'a>(
+        &'a self,
+        pair_list: &'a [(String, Value)],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, Result<BatchResult, Box<dyn Error>>> {
+        Box::pin(self.generate_by_aws(pair_list, source, target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translate::credential::Secret;
+
+    // The IAM `ListUsers` example of the Signature Version 4 documentation
+    #[test]
+    fn signs_the_documented_example() {
+        let credential = AwsCredential {
+            access_key_id: Secret::new("AKIDEXAMPLE".to_string()),
+            secret_access_key: Secret::new("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string()),
+            session_token: None,
+        };
+        let request = SignedRequest {
+            method: "GET",
+            path: "/",
+            query: "Action=ListUsers&Version=2010-05-08",
+            headers: &[
+                (
+                    "content-type",
+                    "application/x-www-form-urlencoded; charset=utf-8",
+                ),
+                ("host", "iam.amazonaws.com"),
+                ("x-amz-date", "20150830T123600Z"),
+            ],
+            payload: b"",
+        };
+        assert_eq!(
+            get_hash(get_canonical_request(&request).as_bytes()),
+            "f536975d06c0309214f805bb90ccff089219ecd68b2577efef23edd43b7e1a59"
+        );
+        assert_eq!(
+            get_authorization(
+                &request,
+                &credential,
+                "us-east-1",
+                "iam",
+                "20150830T123600Z"
+            ),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date, \
+             Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+        );
+    }
+}
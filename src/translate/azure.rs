@@ -0,0 +1,146 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+
+use super::credential::Secret;
+use super::provider::{
+    get_billed_chars, get_text, BatchResult, BoxFuture, HttpError, LengthUnit, ProviderLimits,
+    TranslationProvider,
+};
+
+pub const DEFAULT_ENDPOINT: &str = "https://api.cognitive.microsofttranslator.com";
+
+// Translator v3 limits: 1000 texts and 50000 characters
+const MAX_BATCH_CHARS: usize = 50000;
+const MAX_BATCH_TEXTS: usize = 100;
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct TextItem {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct Translation {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct TextResult {
+    translations: Vec<Translation>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    code: u32,
+    message: String,
+}
+
+// `error` of a response, the code starts with the HTTP status, e.g. 401000 for a wrong key
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+// Azure AI Translator v3
+pub struct AzureProvider {
+    key: Secret,
+    // region of the resource, needed for regional and multi-service resources
+    region: Option<String>,
+    url: Url,
+    client: reqwest::Client,
+}
+
+impl AzureProvider {
+    pub fn new(
+        key: Secret,
+        region: Option<String>,
+        endpoint: &str,
+    ) -> Result<AzureProvider, Box<dyn Error>> {
+        let endpoint = format!("{}/translate", endpoint.trim_end_matches('/'));
+        let url =
+            Url::parse(&endpoint).map_err(|e| format!("Invalid endpoint {}: {}", endpoint, e))?;
+        Ok(AzureProvider {
+            key,
+            region,
+            url,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    async fn generate_by_azure(
+        &self,
+        pair_list: &[(String, Value)],
+        source: &str,
+        target: &str,
+    ) -> Result<BatchResult, Box<dyn Error>> {
+        let body: Vec<TextItem> = pair_list
+            .iter()
+            .map(|x| TextItem {
+                text: get_text(&x.1),
+            })
+            .collect();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Ocp-Apim-Subscription-Key",
+            HeaderValue::from_str(self.key.expose())?,
+        );
+        if let Some(region) = &self.region {
+            headers.insert(
+                "Ocp-Apim-Subscription-Region",
+                HeaderValue::from_str(region)?,
+            );
+        }
+
+        let response = self
+            .client
+            .post(self.url.clone())
+            .query(&[("api-version", "3.0"), ("from", source), ("to", target)])
+            .headers(headers)
+            .json(&body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let e = HttpError::from_response(self.name(), response, |e: ErrorResponse| {
+                (Some(e.error.code.to_string()), e.error.message)
+            });
+            return Err(e.await.into());
+        }
+        let response = response.json::<Vec<TextResult>>().await?;
+        Ok(BatchResult {
+            texts: response
+                .into_iter()
+                .filter_map(|result| result.translations.into_iter().next())
+                .map(|t| Some(t.text))
+                .collect(),
+            used_amount: get_billed_chars(pair_list),
+        })
+    }
+}
+
+impl TranslationProvider for AzureProvider {
+    fn name(&self) -> &str {
+        "Azure Translator"
+    }
+
+    fn limits(&self) -> ProviderLimits {
+        ProviderLimits {
+            max_len: MAX_BATCH_CHARS,
+            unit: LengthUnit::Chars,
+            max_texts: MAX_BATCH_TEXTS,
+        }
+    }
+
+    fn translate<'a>(
+        &'a self,
+        pair_list: &'a [(String, Value)],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, Result<BatchResult, Box<dyn Error>>> {
+        Box::pin(self.generate_by_azure(pair_list, source, target))
+    }
+}
@@ -0,0 +1,260 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+// A secret value that is never printed, `{:?}` and `{}` only show `***`
+#[derive(Clone, Deserialize)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Secret {
+        Secret(value)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "***")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "***")
+    }
+}
+
+#[derive(Debug)]
+pub struct TencentCredential {
+    pub secret_id: Secret,
+    pub secret_key: Secret,
+    // temporary credentials also have a session token
+    pub token: Option<Secret>,
+}
+
+// `~/.tccli/<profile>.credential` written by `tccli configure`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CredentialFile {
+    secret_id: Secret,
+    secret_key: Secret,
+    #[serde(default)]
+    token: Option<Secret>,
+}
+
+fn get_env(name: &str) -> Option<Secret> {
+    env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(Secret::new)
+}
+
+fn get_credential_path(profile: &str) -> Option<PathBuf> {
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+    Some(
+        PathBuf::from(home)
+            .join(".tccli")
+            .join(format!("{}.credential", profile)),
+    )
+}
+
+// Credentials are taken as a whole from the first source that has them: the command line,
+// the `TENCENTCLOUD_*` environment variables, then the Tencent Cloud CLI credential file
+pub fn load_tencent_credential(
+    secret_id: Option<String>,
+    secret_key: Option<String>,
+    token: Option<String>,
+    profile: &str,
+) -> Result<TencentCredential, Box<dyn Error>> {
+    let mut credential = get_tencent_credential(secret_id, secret_key, profile)?;
+    // `--token` goes with credentials from any source, e.g. a secret id and key from STS
+    if let Some(token) = token.filter(|token| !token.is_empty()) {
+        credential.token = Some(Secret::new(token));
+    }
+    Ok(credential)
+}
+
+fn get_tencent_credential(
+    secret_id: Option<String>,
+    secret_key: Option<String>,
+    profile: &str,
+) -> Result<TencentCredential, Box<dyn Error>> {
+    match (secret_id, secret_key) {
+        (Some(secret_id), Some(secret_key)) => {
+            return Ok(TencentCredential {
+                secret_id: Secret::new(secret_id),
+                secret_key: Secret::new(secret_key),
+                token: get_env("TENCENTCLOUD_SESSION_TOKEN"),
+            })
+        }
+        (None, None) => (),
+        _ => {
+            return Err(
+                "Pass both --secret-id and --secret-key (--tencent-secret-id and --tencent-secret-key with translate), or neither of them"
+                    .into(),
+            )
+        }
+    }
+
+    if let (Some(secret_id), Some(secret_key)) = (
+        get_env("TENCENTCLOUD_SECRET_ID"),
+        get_env("TENCENTCLOUD_SECRET_KEY"),
+    ) {
+        return Ok(TencentCredential {
+            secret_id,
+            secret_key,
+            token: get_env("TENCENTCLOUD_SESSION_TOKEN"),
+        });
+    }
+
+    if let Some(path) = get_credential_path(profile).filter(|path| path.is_file()) {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read credential file {}: {}", path.display(), e))?;
+        // serde messages may quote the invalid value, only the position is reported
+        let file: CredentialFile = serde_json::from_str(&content).map_err(|e| {
+            format!(
+                "Invalid credential file {} at line {} column {}, expected secretId and secretKey",
+                path.display(),
+                e.line(),
+                e.column()
+            )
+        })?;
+        return Ok(TencentCredential {
+            secret_id: file.secret_id,
+            secret_key: file.secret_key,
+            token: file.token.filter(|token| !token.expose().is_empty()),
+        });
+    }
+
+    Err(format!(
+        "No Tencent Cloud credentials found, set TENCENTCLOUD_SECRET_ID and TENCENTCLOUD_SECRET_KEY, or run `tccli configure` to create ~/.tccli/{}.credential",
+        profile
+    )
+    .into())
+}
+
+// The DeepL API key from the command line or the `DEEPL_AUTH_KEY` environment variable
+pub fn load_deepl_auth_key(auth_key: Option<String>) -> Result<Secret, Box<dyn Error>> {
+    auth_key
+        .filter(|auth_key| !auth_key.is_empty())
+        .map(Secret::new)
+        .or_else(|| get_env("DEEPL_AUTH_KEY"))
+        .ok_or_else(|| "No DeepL API key found, set DEEPL_AUTH_KEY".into())
+}
+
+// The API key from the command line or the `OPENAI_API_KEY` environment variable, local servers
+// like Ollama do not need one
+pub fn load_openai_api_key(api_key: Option<String>) -> Option<Secret> {
+    api_key
+        .filter(|api_key| !api_key.is_empty())
+        .map(Secret::new)
+        .or_else(|| get_env("OPENAI_API_KEY"))
+}
+
+// The key of an Azure Translator or multi-service resource
+pub fn load_azure_key(key: Option<String>) -> Result<Secret, Box<dyn Error>> {
+    key.filter(|key| !key.is_empty())
+        .map(Secret::new)
+        .or_else(|| get_env("AZURE_TRANSLATOR_KEY"))
+        .ok_or_else(|| "No Azure Translator key found, set AZURE_TRANSLATOR_KEY".into())
+}
+
+// An OAuth access token from the command line, `GOOGLE_OAUTH_ACCESS_TOKEN`, or the signed in
+// Google Cloud CLI. Tokens expire after an hour.
+pub fn load_google_access_token(token: Option<String>) -> Result<Secret, Box<dyn Error>> {
+    if let Some(token) = token
+        .filter(|token| !token.is_empty())
+        .map(Secret::new)
+        .or_else(|| get_env("GOOGLE_OAUTH_ACCESS_TOKEN"))
+    {
+        return Ok(token);
+    }
+    let output = Command::new("gcloud")
+        .args(["auth", "print-access-token"])
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            Ok(Secret::new(token))
+        }
+        _ => Err("No Google Cloud access token found, set GOOGLE_OAUTH_ACCESS_TOKEN or sign in with `gcloud auth login`".into()),
+    }
+}
+
+#[derive(Debug)]
+pub struct AwsCredential {
+    pub access_key_id: Secret,
+    pub secret_access_key: Secret,
+    pub session_token: Option<Secret>,
+}
+
+// The `[profile]` section of `~/.aws/credentials`, only `key = value` lines are read
+fn parse_aws_credential_file(content: &str, profile: &str) -> Option<AwsCredential> {
+    let mut section = None;
+    let mut values = HashMap::new();
+    for line in content.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = Some(name.trim());
+        } else if section == Some(profile) {
+            if let Some((key, value)) = line.split_once('=') {
+                values.insert(key.trim(), value.trim());
+            }
+        }
+    }
+    let mut get = |key| {
+        values
+            .remove(key)
+            .filter(|value: &&str| !value.is_empty())
+            .map(|value| Secret::new(value.to_string()))
+    };
+    Some(AwsCredential {
+        access_key_id: get("aws_access_key_id")?,
+        secret_access_key: get("aws_secret_access_key")?,
+        session_token: get("aws_session_token"),
+    })
+}
+
+// The `AWS_*` environment variables, then the profile of the AWS CLI credential file
+pub fn load_aws_credential(profile: &str) -> Result<AwsCredential, Box<dyn Error>> {
+    if let (Some(access_key_id), Some(secret_access_key)) = (
+        get_env("AWS_ACCESS_KEY_ID"),
+        get_env("AWS_SECRET_ACCESS_KEY"),
+    ) {
+        return Ok(AwsCredential {
+            access_key_id,
+            secret_access_key,
+            session_token: get_env("AWS_SESSION_TOKEN"),
+        });
+    }
+    let path = env::var_os("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .or_else(|| {
+            let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+            Some(PathBuf::from(home).join(".aws").join("credentials"))
+        });
+    if let Some(path) = path.filter(|path| path.is_file()) {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read credential file {}: {}", path.display(), e))?;
+        return parse_aws_credential_file(&content, profile).ok_or_else(|| {
+            format!(
+                "No aws_access_key_id and aws_secret_access_key in profile {} of {}",
+                profile,
+                path.display()
+            )
+            .into()
+        });
+    }
+    Err("No AWS credentials found, set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, or run `aws configure` to create ~/.aws/credentials".into())
+}
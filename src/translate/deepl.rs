@@ -0,0 +1,209 @@
+use clap::ValueEnum;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+
+use super::credential::Secret;
+use super::provider::{
+    get_text, BatchResult, BoxFuture, HttpError, LengthUnit, ProviderLimits, TranslationProvider,
+};
+
+// Keys of the free plan end with `:fx` and only work with the free endpoint
+pub const FREE_ENDPOINT: &str = "https://api-free.deepl.com/v2/translate";
+pub const PRO_ENDPOINT: &str = "https://api.deepl.com/v2/translate";
+
+// A request may have 50 texts and 128 KiB, a character takes at most 4 bytes in UTF-8 and the
+// rest of the body stays well below the remaining space
+const MAX_BATCH_CHARS: usize = 30000;
+const MAX_BATCH_TEXTS: usize = 50;
+
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Formality {
+    /// formality of the target language by default
+    Default,
+    /// formal, fails for target languages without formality
+    More,
+    /// informal, fails for target languages without formality
+    Less,
+    /// formal where the target language supports it
+    PreferMore,
+    /// informal where the target language supports it
+    PreferLess,
+}
+
+impl Formality {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Formality::Default => "default",
+            Formality::More => "more",
+            Formality::Less => "less",
+            Formality::PreferMore => "prefer_more",
+            Formality::PreferLess => "prefer_less",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagHandling {
+    /// keep XML tags, e.g. `<b>{count}</b>`
+    Xml,
+    /// keep HTML tags
+    Html,
+}
+
+impl TagHandling {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TagHandling::Xml => "xml",
+            TagHandling::Html => "html",
+        }
+    }
+}
+
+pub struct DeeplOptions {
+    pub formality: Option<Formality>,
+    // a glossary is made for one language pair, `source` and `target` have to match it
+    pub glossary_id: Option<String>,
+    pub tag_handling: Option<TagHandling>,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    text: Vec<String>,
+    source_lang: String,
+    target_lang: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formality: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    glossary_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag_handling: Option<&'a str>,
+    show_billed_characters: bool,
+}
+
+#[derive(Deserialize)]
+struct Translation {
+    text: String,
+    #[serde(default)]
+    billed_characters: u32,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    translations: Vec<Translation>,
+}
+
+// The body of an error status, e.g. 403 for a wrong key or 456 when the quota is used up
+#[derive(Deserialize)]
+struct ErrorResponse {
+    message: String,
+}
+
+pub struct DeeplProvider {
+    auth_key: Secret,
+    url: Url,
+    options: DeeplOptions,
+    client: reqwest::Client,
+}
+
+impl DeeplProvider {
+    // Without `endpoint` the free or pro endpoint is chosen by the key
+    pub fn new(
+        auth_key: Secret,
+        endpoint: Option<&str>,
+        options: DeeplOptions,
+    ) -> Result<DeeplProvider, Box<dyn Error>> {
+        let endpoint = match endpoint {
+            Some(endpoint) => endpoint,
+            None if auth_key.expose().ends_with(":fx") => FREE_ENDPOINT,
+            None => PRO_ENDPOINT,
+        };
+        let url =
+            Url::parse(endpoint).map_err(|e| format!("Invalid endpoint {}: {}", endpoint, e))?;
+        Ok(DeeplProvider {
+            auth_key,
+            url,
+            options,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    async fn generate_by_deepl(
+        &self,
+        pair_list: &[(String, Value)],
+        source: &str,
+        target: &str,
+    ) -> Result<BatchResult, Box<dyn Error>> {
+        // DeepL language codes are upper case, e.g. `ZH` or `EN-US`
+        let request = Request {
+            text: pair_list.iter().map(|x| get_text(&x.1)).collect(),
+            source_lang: source.to_uppercase(),
+            target_lang: target.to_uppercase(),
+            formality: self.options.formality.map(|f| f.as_str()),
+            glossary_id: self.options.glossary_id.as_deref(),
+            tag_handling: self.options.tag_handling.map(|t| t.as_str()),
+            show_billed_characters: true,
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Authorization",
+            HeaderValue::from_str(&format!("DeepL-Auth-Key {}", self.auth_key.expose()))?,
+        );
+
+        let response = self
+            .client
+            .post(self.url.clone())
+            .headers(headers)
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let e = HttpError::from_response(self.name(), response, |e: ErrorResponse| {
+                (None, e.message)
+            });
+            return Err(e.await.into());
+        }
+        let response = response.json::<Response>().await?;
+        Ok(BatchResult {
+            used_amount: response
+                .translations
+                .iter()
+                .map(|t| t.billed_characters)
+                .sum(),
+            texts: response
+                .translations
+                .into_iter()
+                .map(|t| Some(t.text))
+                .collect(),
+        })
+    }
+}
+
+impl TranslationProvider for DeeplProvider {
+    fn name(&self) -> &str {
+        "DeepL"
+    }
+
+    fn limits(&self) -> ProviderLimits {
+        ProviderLimits {
+            max_len: MAX_BATCH_CHARS,
+            unit: LengthUnit::Chars,
+            max_texts: MAX_BATCH_TEXTS,
+        }
+    }
+
+    fn translate<'a>(
+        &'a self,
+        pair_list: &'a [(String, Value)],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, Result<BatchResult, Box<dyn Error>>> {
+        Box::pin(self.generate_by_deepl(pair_list, source, target))
+    }
+}
@@ -0,0 +1,149 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+
+use super::credential::Secret;
+use super::provider::{
+    get_billed_chars, get_text, BatchResult, BoxFuture, HttpError, LengthUnit, ProviderLimits,
+    TranslationProvider,
+};
+
+pub const DEFAULT_ENDPOINT: &str = "https://translation.googleapis.com";
+pub const DEFAULT_LOCATION: &str = "global";
+
+// `translateText` limits: 1024 texts and 30000 code points
+const MAX_BATCH_CHARS: usize = 30000;
+const MAX_BATCH_TEXTS: usize = 100;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Request<'a> {
+    contents: Vec<String>,
+    source_language_code: &'a str,
+    target_language_code: &'a str,
+    mime_type: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Translation {
+    translated_text: String,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    translations: Vec<Translation>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+    #[serde(default)]
+    status: String,
+}
+
+// `error` of a response, e.g. `PERMISSION_DENIED` when the API is not enabled
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+// Google Cloud Translation v3, texts are sent with `translateText`
+pub struct GoogleProvider {
+    access_token: Secret,
+    project: String,
+    url: Url,
+    client: reqwest::Client,
+}
+
+impl GoogleProvider {
+    pub fn new(
+        access_token: Secret,
+        project: String,
+        location: &str,
+        endpoint: &str,
+    ) -> Result<GoogleProvider, Box<dyn Error>> {
+        let endpoint = format!(
+            "{}/v3/projects/{}/locations/{}:translateText",
+            endpoint.trim_end_matches('/'),
+            project,
+            location
+        );
+        let url =
+            Url::parse(&endpoint).map_err(|e| format!("Invalid endpoint {}: {}", endpoint, e))?;
+        Ok(GoogleProvider {
+            access_token,
+            project,
+            url,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    async fn generate_by_google(
+        &self,
+        pair_list: &[(String, Value)],
+        source: &str,
+        target: &str,
+    ) -> Result<BatchResult, Box<dyn Error>> {
+        let request = Request {
+            contents: pair_list.iter().map(|x| get_text(&x.1)).collect(),
+            source_language_code: source,
+            target_language_code: target,
+            mime_type: "text/plain",
+        };
+
+        let response = self
+            .client
+            .post(self.url.clone())
+            .bearer_auth(self.access_token.expose())
+            // bill the project of the request, also with the credentials of a user
+            .header("x-goog-user-project", &self.project)
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let e = HttpError::from_response(self.name(), response, |e: ErrorResponse| {
+                (
+                    Some(e.error.status).filter(|s| !s.is_empty()),
+                    e.error.message,
+                )
+            });
+            return Err(e.await.into());
+        }
+        let response = response.json::<Response>().await?;
+        Ok(BatchResult {
+            texts: response
+                .translations
+                .into_iter()
+                .map(|t| Some(t.translated_text))
+                .collect(),
+            used_amount: get_billed_chars(pair_list),
+        })
+    }
+}
+
+impl TranslationProvider for GoogleProvider {
+    fn name(&self) -> &str {
+        "Google Cloud Translation"
+    }
+
+    fn limits(&self) -> ProviderLimits {
+        ProviderLimits {
+            max_len: MAX_BATCH_CHARS,
+            unit: LengthUnit::Chars,
+            max_texts: MAX_BATCH_TEXTS,
+        }
+    }
+
+    fn translate<'a>(
+        &'a self,
+        pair_list: &'a [(String, Value)],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, Result<BatchResult, Box<dyn Error>>> {
+        Box::pin(self.generate_by_google(pair_list, source, target))
+    }
+}
@@ -1,2 +1,11 @@
+pub mod aws;
+pub mod azure;
+pub mod credential;
+pub mod deepl;
+pub mod google;
+pub mod openai;
+pub mod provider;
+pub mod retry;
 pub mod tencent;
+#[allow(clippy::module_inception)]
 pub mod translate;
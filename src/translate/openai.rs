@@ -0,0 +1,329 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::error::Error;
+use std::fmt;
+
+use super::credential::Secret;
+use super::provider::{
+    get_text, BatchResult, BoxFuture, HttpError, LengthUnit, ProviderLimits, TranslationProvider,
+};
+use super::retry::{is_retryable_http, is_retryable_status};
+
+// Base URL of the API, `/chat/completions` is appended, e.g. `http://localhost:11434/v1` for
+// Ollama or `http://localhost:8080/v1` for the llama.cpp server
+pub const DEFAULT_ENDPOINT: &str = "https://api.openai.com/v1";
+pub const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+// Small batches keep the answer short enough for the output token limit of most models
+const MAX_BATCH_CHARS: usize = 4000;
+const MAX_BATCH_TEXTS: usize = 20;
+
+const SYSTEM_PROMPT: &str = "You translate the texts of a software user interface. \
+The user sends a JSON object with the source and target language, an optional context and the texts, \
+each with a key and its placeholders. Reply with a JSON object {\"translations\": {\"<key>\": \"<translated text>\"}} \
+that has every key of the request. Keep every placeholder of a text exactly as it is, e.g. {name} or {{name}}. \
+In ICU messages like {count, plural, one {# item} other {# items}} only translate the text inside the branches.";
+
+// Placeholders of a text: `{name}` and `{{name}}`, ICU arguments like `{count, plural, ...}` as
+// `{count}`. Sorted, a translation has to have the same ones.
+pub fn get_placeholders(text: &str) -> Vec<String> {
+    let mut placeholders = vec![];
+    let mut depth = 0;
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        if depth == 0 && text[i..].starts_with("{{") {
+            if let Some(end) = text[i..].find("}}") {
+                placeholders.push(text[i..i + end + 2].to_string());
+                i += end + 2;
+                continue;
+            }
+        }
+        match c {
+            '{' => {
+                if depth == 0 {
+                    let rest = &text[i + 1..];
+                    let end = rest.find([',', '}']).unwrap_or(rest.len());
+                    placeholders.push(format!("{{{}}}", rest[..end].trim()));
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => depth -= 1,
+            _ => (),
+        }
+        i += c.len_utf8();
+    }
+    placeholders.sort();
+    placeholders
+}
+
+const SERVICE: &str = "Chat Completions";
+
+// An answer that is not the JSON asked for
+#[derive(Debug)]
+pub struct OpenAiError {
+    pub message: String,
+}
+
+impl fmt::Display for OpenAiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} Error: {}", SERVICE, self.message)
+    }
+}
+
+impl Error for OpenAiError {}
+
+impl OpenAiError {
+    fn invalid_response(message: String) -> OpenAiError {
+        OpenAiError { message }
+    }
+}
+
+#[derive(Serialize)]
+struct TextItem<'a> {
+    key: &'a str,
+    text: String,
+    placeholders: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Deserialize, Default)]
+struct Usage {
+    total_tokens: u32,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+    // e.g. `insufficient_quota`
+    #[serde(default)]
+    code: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+#[derive(Deserialize)]
+struct Translations {
+    translations: Map<String, Value>,
+}
+
+pub struct OpenAiProvider {
+    api_key: Option<Secret>,
+    url: Url,
+    model: String,
+    // what the texts are about, e.g. `checkout page of an online shop`
+    context: Option<String>,
+    client: reqwest::Client,
+}
+
+impl OpenAiProvider {
+    pub fn new(
+        api_key: Option<Secret>,
+        endpoint: &str,
+        model: String,
+        context: Option<String>,
+    ) -> Result<OpenAiProvider, Box<dyn Error>> {
+        let endpoint = format!("{}/chat/completions", endpoint.trim_end_matches('/'));
+        let url =
+            Url::parse(&endpoint).map_err(|e| format!("Invalid endpoint {}: {}", endpoint, e))?;
+        Ok(OpenAiProvider {
+            api_key,
+            url,
+            model,
+            context,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    fn get_messages(&self, items: &[TextItem], source: &str, target: &str) -> Value {
+        let mut request = json!({
+            "source_language": source,
+            "target_language": target,
+            "texts": items,
+        });
+        if let Some(context) = &self.context {
+            request["context"] = Value::String(context.to_string());
+        }
+        json!([
+            { "role": "system", "content": SYSTEM_PROMPT },
+            { "role": "user", "content": request.to_string() },
+        ])
+    }
+
+    async fn generate_by_openai(
+        &self,
+        pair_list: &[(String, Value)],
+        source: &str,
+        target: &str,
+    ) -> Result<BatchResult, Box<dyn Error>> {
+        let items: Vec<TextItem> = pair_list
+            .iter()
+            .map(|(key, value)| {
+                let text = get_text(value);
+                TextItem {
+                    key,
+                    placeholders: get_placeholders(&text),
+                    text,
+                }
+            })
+            .collect();
+        let body = json!({
+            "model": self.model,
+            "messages": self.get_messages(&items, source, target),
+            "temperature": 0,
+            "response_format": { "type": "json_object" },
+        });
+
+        let mut request = self.client.post(self.url.clone()).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key.expose());
+        }
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            let e = HttpError::from_response(SERVICE, response, |e: ErrorResponse| {
+                (e.error.code, e.error.message)
+            });
+            return Err(e.await.into());
+        }
+        let response = response.json::<Response>().await?;
+        let content = response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .unwrap_or_default();
+        // some models wrap the JSON in a markdown code block
+        let content = content
+            .trim()
+            .trim_start_matches("```json")
+            .trim_start_matches("```")
+            .trim_end_matches("```");
+        let translations = serde_json::from_str::<Translations>(content)
+            .map_err(|e| OpenAiError::invalid_response(format!("invalid JSON answer, {}", e)))?
+            .translations;
+        if !items.iter().any(|item| translations.contains_key(item.key)) {
+            return Err(OpenAiError::invalid_response(
+                "the answer has none of the keys of the request".to_string(),
+            )
+            .into());
+        }
+
+        // texts that are missing or lost placeholders are skipped, the next run sends them again
+        let texts = items
+            .into_iter()
+            .map(|item| match translations.get(item.key).and_then(Value::as_str) {
+                Some(text) if get_placeholders(text) == item.placeholders => {
+                    Some(text.to_string())
+                }
+                Some(text) => {
+                    eprintln!(
+                        "Warning: Translation of key {} changed the placeholders {:?} to {:?}, it is skipped",
+                        item.key,
+                        item.placeholders,
+                        get_placeholders(text)
+                    );
+                    None
+                }
+                None => {
+                    eprintln!("Warning: No translation of key {}, it is skipped", item.key);
+                    None
+                }
+            })
+            .collect();
+        Ok(BatchResult {
+            texts,
+            used_amount: response.usage.unwrap_or_default().total_tokens,
+        })
+    }
+}
+
+impl TranslationProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        &self.model
+    }
+
+    fn limits(&self) -> ProviderLimits {
+        ProviderLimits {
+            max_len: MAX_BATCH_CHARS,
+            unit: LengthUnit::Chars,
+            max_texts: MAX_BATCH_TEXTS,
+        }
+    }
+
+    // Invalid answers too, a model may answer correctly next time. 429 is also sent when the
+    // quota is used up, that does not pass.
+    fn is_retryable(&self, err: &(dyn Error + 'static)) -> bool {
+        if err.is::<OpenAiError>() {
+            return true;
+        }
+        match err.downcast_ref::<HttpError>() {
+            Some(e) => {
+                is_retryable_status(e.status) && e.code.as_deref() != Some("insufficient_quota")
+            }
+            None => is_retryable_http(err),
+        }
+    }
+
+    fn translate<'a>(
+        &'a self,
+        pair_list: &'a [(String, Value)],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, Result<BatchResult, Box<dyn Error>>> {
+        Box::pin(self.generate_by_openai(pair_list, source, target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gets_sorted_placeholders() {
+        assert_eq!(
+            get_placeholders("{name} has {count} 个"),
+            ["{count}", "{name}"]
+        );
+        assert_eq!(get_placeholders("{{ total }} items"), ["{{ total }}"]);
+        assert!(get_placeholders("no placeholders } here").is_empty());
+    }
+
+    #[test]
+    fn gets_the_argument_of_icu_messages() {
+        assert_eq!(
+            get_placeholders("{count, plural, one {# item of {name}} other {# items}}"),
+            ["{count}"]
+        );
+        assert_eq!(
+            get_placeholders("{gender, select, male {He} other {They}} said {{word}}"),
+            ["{gender}", "{{word}}"]
+        );
+    }
+
+    // the rest of the text after an unbalanced `{` is one placeholder, so it has to be kept
+    #[test]
+    fn gets_unbalanced_braces() {
+        assert_eq!(get_placeholders("Total: {amount"), ["{amount}"]);
+        assert_eq!(get_placeholders("Open {{ brace"), ["{{ brace}"]);
+    }
+}
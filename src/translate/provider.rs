@@ -0,0 +1,248 @@
+use reqwest::{Response, Url};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::ops::Range;
+use std::pin::Pin;
+
+use super::retry::is_retryable_http;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+// How the length of texts is measured against `ProviderLimits::max_len`
+#[derive(Clone, Copy, PartialEq)]
+pub enum LengthUnit {
+    Chars,
+    // bytes of the UTF-8 text
+    Bytes,
+}
+
+// How much one request of a translation service can hold
+pub struct ProviderLimits {
+    // length of all texts of a request, at most
+    pub max_len: usize,
+    pub unit: LengthUnit,
+    // texts of a request, at most. Services that take many more get a lower count, which keeps
+    // a single request, and the work lost when it fails, small.
+    pub max_texts: usize,
+}
+
+pub struct BatchResult {
+    // translated texts, in the order of the texts sent. `None` for a text that was skipped, it
+    // is not written to the output so the next run translates it again.
+    pub texts: Vec<Option<String>>,
+    // characters, or tokens of language models, charged by the translation service
+    pub used_amount: u32,
+}
+
+// A request answered with an error status. `code` is the error name or number the body of the
+// service gives, e.g. `PERMISSION_DENIED` of Google Cloud Translation.
+#[derive(Debug)]
+pub struct HttpError {
+    // e.g. `DeepL`
+    pub service: String,
+    pub status: u16,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.code {
+            Some(code) => write!(
+                f,
+                "{} Error: [{} {}] {}",
+                self.service, self.status, code, self.message
+            ),
+            None => write!(
+                f,
+                "{} Error: [{}] {}",
+                self.service, self.status, self.message
+            ),
+        }
+    }
+}
+
+impl Error for HttpError {}
+
+impl HttpError {
+    // Read the error of a response that is not a success. `parse` gets the code and message out
+    // of the error body `T` of the service, other bodies, e.g. of a proxy, keep the status reason.
+    pub async fn from_response<T: DeserializeOwned>(
+        service: &str,
+        response: Response,
+        parse: impl FnOnce(T) -> (Option<String>, String),
+    ) -> HttpError {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        let (code, message) = match serde_json::from_str::<T>(&body) {
+            Ok(e) => parse(e),
+            Err(_) => (
+                None,
+                status.canonical_reason().unwrap_or_default().to_string(),
+            ),
+        };
+        HttpError {
+            service: service.to_string(),
+            status: status.as_u16(),
+            code,
+            message,
+        }
+    }
+}
+
+// A translation service. `translate` sends one batch that fits in `limits`, batching, retries
+// and rate limiting are done by the caller.
+pub trait TranslationProvider {
+    // shown in messages, e.g. `Tencent`
+    fn name(&self) -> &str;
+
+    fn limits(&self) -> ProviderLimits;
+
+    // Whether a request that failed with `err` may succeed when sent again
+    fn is_retryable(&self, err: &(dyn Error + 'static)) -> bool {
+        is_retryable_http(err)
+    }
+
+    // Translate the values of `pair_list` from `source` to `target`, the keys are only used
+    // in messages or as context
+    fn translate<'a>(
+        &'a self,
+        pair_list: &'a [(String, Value)],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, Result<BatchResult, Box<dyn Error>>>;
+}
+
+// Parse an endpoint URL, a bare host name is served over https. The host is returned with the
+// port if there is one, as the `Host` header that signatures cover.
+pub fn parse_endpoint(endpoint: &str) -> Result<(Url, String), Box<dyn Error>> {
+    let endpoint = if endpoint.contains("://") {
+        endpoint.to_string()
+    } else {
+        format!("https://{}", endpoint)
+    };
+    let url = Url::parse(&endpoint).map_err(|e| format!("Invalid endpoint {}: {}", endpoint, e))?;
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err(format!("Invalid endpoint {}: no host", endpoint).into()),
+    };
+    Ok((url, host))
+}
+
+// Values that are not strings are translated as their JSON text
+pub fn get_text(value: &Value) -> String {
+    match value.as_str() {
+        Some(text) => text.to_string(),
+        None => value.to_string(),
+    }
+}
+
+pub fn get_text_len(value: &Value) -> usize {
+    match value.as_str() {
+        Some(text) => text.chars().count(),
+        None => value.to_string().chars().count(),
+    }
+}
+
+// The amount of services that charge by the characters sent
+pub fn get_billed_chars(pair_list: &[(String, Value)]) -> u32 {
+    pair_list.iter().map(|x| get_text_len(&x.1) as u32).sum()
+}
+
+// Split the texts into consecutive batches that fit in one request each
+pub fn get_batches(
+    pair_list: &[(String, Value)],
+    limits: &ProviderLimits,
+) -> Result<Vec<Range<usize>>, Box<dyn Error>> {
+    let mut batches = vec![];
+    let mut start = 0;
+    let mut total = 0;
+    for (i, (key, value)) in pair_list.iter().enumerate() {
+        let (len, unit) = match limits.unit {
+            LengthUnit::Chars => (get_text_len(value), "characters"),
+            LengthUnit::Bytes => (get_text(value).len(), "bytes"),
+        };
+        if len > limits.max_len {
+            return Err(format!(
+                "Text of key {} is {} {} long, the limit is {}",
+                key, len, unit, limits.max_len
+            )
+            .into());
+        }
+        if total + len > limits.max_len || i - start == limits.max_texts {
+            batches.push(start..i);
+            start = i;
+            total = 0;
+        }
+        total += len;
+    }
+    if start < pair_list.len() {
+        batches.push(start..pair_list.len());
+    }
+    Ok(batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_pair_list(texts: &[&str]) -> Vec<(String, Value)> {
+        texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| (i.to_string(), Value::String(text.to_string())))
+            .collect()
+    }
+
+    fn limits(max_len: usize, unit: LengthUnit, max_texts: usize) -> ProviderLimits {
+        ProviderLimits {
+            max_len,
+            unit,
+            max_texts,
+        }
+    }
+
+    #[test]
+    fn starts_a_batch_at_max_texts() {
+        let pair_list = get_pair_list(&["a", "b", "c", "d", "e"]);
+        let batches = get_batches(&pair_list, &limits(100, LengthUnit::Chars, 2)).unwrap();
+        assert_eq!(batches, [0..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn starts_a_batch_when_the_length_would_pass_max_len() {
+        let pair_list = get_pair_list(&["aaa", "bb", "c", "dddd"]);
+        let batches = get_batches(&pair_list, &limits(6, LengthUnit::Chars, 10)).unwrap();
+        assert_eq!(batches, [0..3, 3..4]);
+    }
+
+    #[test]
+    fn counts_chars_or_utf8_bytes() {
+        let pair_list = get_pair_list(&["你好", "世界"]);
+        let chars = get_batches(&pair_list, &limits(4, LengthUnit::Chars, 10)).unwrap();
+        assert_eq!(chars.len(), 1);
+        assert_eq!(chars[0], 0..2);
+        let bytes = get_batches(&pair_list, &limits(6, LengthUnit::Bytes, 10)).unwrap();
+        assert_eq!(bytes, [0..1, 1..2]);
+    }
+
+    #[test]
+    fn fails_on_a_text_over_max_len() {
+        let pair_list = get_pair_list(&["a", "bbbb"]);
+        let err = get_batches(&pair_list, &limits(3, LengthUnit::Chars, 10)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Text of key 1 is 4 characters long, the limit is 3"
+        );
+    }
+
+    #[test]
+    fn returns_no_batches_without_texts() {
+        let batches = get_batches(&[], &limits(3, LengthUnit::Chars, 10)).unwrap();
+        assert!(batches.is_empty());
+    }
+}
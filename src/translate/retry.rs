@@ -0,0 +1,115 @@
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tokio::time::{sleep, sleep_until, Instant};
+
+use super::provider::HttpError;
+
+pub const DEFAULT_RETRIES: u32 = 5;
+pub const DEFAULT_RPS: f64 = 5.0;
+
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(20);
+
+pub struct RequestPolicy {
+    // retries of a request that failed with a retryable error
+    pub retries: u32,
+    // requests sent per second at most
+    pub rps: f64,
+}
+
+// A random number in [0, 1) without pulling in a random number crate, `RandomState` is seeded
+// randomly for every instance
+fn get_random() -> f64 {
+    let hash = RandomState::new().build_hasher().finish();
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl RequestPolicy {
+    // Exponential backoff with jitter, between half and all of `BASE_DELAY * 2^attempt`, so
+    // clients that failed together do not retry together
+    fn get_delay(&self, attempt: u32) -> Duration {
+        let delay = BASE_DELAY
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_DELAY);
+        delay.mul_f64(0.5 + get_random() / 2.0)
+    }
+}
+
+// Spaces requests evenly, at most `rps` requests are started per second
+pub struct RateLimiter {
+    interval: Duration,
+    next: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rps: f64) -> RateLimiter {
+        RateLimiter {
+            interval: Duration::from_secs_f64(1.0 / rps),
+            next: Instant::now(),
+        }
+    }
+
+    pub async fn wait(&mut self) {
+        sleep_until(self.next).await;
+        self.next = Instant::now() + self.interval;
+    }
+}
+
+// Rate limits and server errors, including DeepL's 529 when it is overloaded. Quota errors like
+// DeepL's 456 do not go away by waiting.
+pub fn is_retryable_status(status: u16) -> bool {
+    status == 429 || status >= 500
+}
+
+// Network errors, timeouts and 429 / 5xx responses, the failures of any HTTP service that
+// may go away when the request is sent again
+pub fn is_retryable_http(err: &(dyn Error + 'static)) -> bool {
+    if let Some(e) = err.downcast_ref::<HttpError>() {
+        return is_retryable_status(e.status);
+    }
+    match err.downcast_ref::<reqwest::Error>() {
+        Some(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status()
+                    .is_some_and(|status| is_retryable_status(status.as_u16()))
+        }
+        None => false,
+    }
+}
+
+// Send a request, retrying it while it fails with an error `is_retryable` accepts
+pub async fn send_with_retry<T, F, Fut>(
+    policy: &RequestPolicy,
+    limiter: &mut RateLimiter,
+    is_retryable: impl Fn(&(dyn Error + 'static)) -> bool,
+    mut send: F,
+) -> Result<T, Box<dyn Error>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Box<dyn Error>>>,
+{
+    let mut attempt = 0;
+    loop {
+        limiter.wait().await;
+        match send().await {
+            Ok(result) => return Ok(result),
+            Err(e) if attempt < policy.retries && is_retryable(e.as_ref()) => {
+                let delay = policy.get_delay(attempt);
+                attempt += 1;
+                eprintln!(
+                    "Warning: {}, retry {}/{} in {:.1}s",
+                    e,
+                    attempt,
+                    policy.retries,
+                    delay.as_secs_f64()
+                );
+                sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
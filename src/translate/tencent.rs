@@ -1,70 +1,73 @@
 use chrono::{DateTime, TimeZone, Utc};
 use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Url;
 use serde::Deserialize;
 use serde_json::{to_string, Map, Value};
+use std::error::Error;
+use std::fmt;
 
 use crate::utils::hash::{get_hash, sha256};
 
-use super::translate::{Payload, Translate};
+use super::credential::TencentCredential;
+use super::provider::{
+    parse_endpoint, BatchResult, BoxFuture, LengthUnit, ProviderLimits, TranslationProvider,
+};
+use super::retry::is_retryable_http;
+
+pub const DEFAULT_ENDPOINT: &str = "https://tmt.tencentcloudapi.com";
+pub const DEFAULT_REGION: &str = "ap-shanghai";
+
+// `TextTranslateBatch` limits: the texts of one request must be shorter than 6000 characters
+// in total
+const MAX_BATCH_CHARS: usize = 5999;
+const MAX_BATCH_TEXTS: usize = 100;
+
+// Where requests are sent, e.g. `https://tmt.ap-singapore.tencentcloudapi.com` or a local mock
+// server like `http://127.0.0.1:8080`
+pub struct TencentEndpoint {
+    url: Url,
+    // `Host` header, also part of the signature
+    host: String,
+    region: String,
+}
+
+impl TencentEndpoint {
+    pub fn new(endpoint: &str, region: String) -> Result<TencentEndpoint, Box<dyn Error>> {
+        let (url, host) = parse_endpoint(endpoint)?;
+        Ok(TencentEndpoint { url, host, region })
+    }
+}
 
 fn get_date(timestamp: i64) -> String {
     let dt: DateTime<Utc> = Utc.timestamp_opt(timestamp, 0).unwrap();
     dt.format("%Y-%m-%d").to_string()
 }
 
-#[derive(Deserialize, Debug)]
-pub struct ResponseError {
-    pub Code: String,
-    pub Message: String,
-}
-#[derive(Deserialize, Debug)]
-pub struct Response {
-    pub RequestId: String,
-    pub Source: String,
-    pub Target: String,
-    pub TargetTextList: Vec<String>,
-    pub UsedAmount: u32,
-    pub Error: Option<ResponseError>,
-}
+const CONTENT_TYPE: &str = "application/json; charset=utf-8";
 
-#[derive(Deserialize, Debug)]
-pub struct RequestResponse {
-    pub Response: Response,
-}
-#[tokio::main]
-pub async fn generate_by_tencent<T: Payload>(
-    translate: &Translate<T>,
-    pair_list: &Vec<(String, Value)>,
-    secret_id: &str,
-    secret_key: &str,
-) -> Result<RequestResponse, Box<dyn std::error::Error>> {
-    const TOKEN: &str = "";
-    const HOST: &str = "tmt.tencentcloudapi.com";
-    const SERVICE: &str = "tmt";
-    const REGION: &str = "ap-shanghai";
-    const ACTION: &str = "TextTranslateBatch";
-    const VERSION: &str = "2018-03-21";
-
-    let timestamp = Utc::now().timestamp();
+// `Authorization` header of a POST request signed with TC3-HMAC-SHA256, the `Content-Type` and
+// `Host` headers are signed
+fn get_authorization(
+    credential: &TencentCredential,
+    host: &str,
+    path: &str,
+    payload: &[u8],
+    timestamp: i64,
+    service: &str,
+) -> String {
     let date = get_date(timestamp);
 
-    let payload = translate.payload.to_string(pair_list);
-
     // Step 1: Build canonical request
     let signed_headers = "content-type;host";
-    let hashed_request_payload = get_hash(payload.as_bytes());
+    let hashed_request_payload = get_hash(payload);
     let http_request_method = "POST";
-    let canonical_uri = "/";
     let canonical_query_string = "";
-    let canonical_headers = format!(
-        "content-type:application/json; charset=utf-8\nhost:{}\n",
-        HOST
-    );
+    let canonical_headers = format!("content-type:{}\nhost:{}\n", CONTENT_TYPE, host);
 
     let canonical_request = format!(
         "{}\n{}\n{}\n{}\n{}\n{}",
         http_request_method,
-        canonical_uri,
+        path,
         canonical_query_string,
         canonical_headers,
         signed_headers,
@@ -74,105 +77,267 @@ pub async fn generate_by_tencent<T: Payload>(
     // Step 2: Build string to sign
     let algorithm = "TC3-HMAC-SHA256";
     let hashed_canonical_request = get_hash(canonical_request.as_bytes());
-    let credential_scope = format!("{}/{}/tc3_request", date, SERVICE);
+    let credential_scope = format!("{}/{}/tc3_request", date, service);
     let string_to_sign = format!(
         "{}\n{}\n{}\n{}",
         algorithm, timestamp, credential_scope, hashed_canonical_request
     );
 
     // Step 3: Calculate signature
-    let k_date = sha256(date.as_bytes(), format!("TC3{}", secret_key).as_bytes());
-    let k_service = sha256(SERVICE.as_bytes(), &k_date);
+    let k_date = sha256(
+        date.as_bytes(),
+        format!("TC3{}", credential.secret_key.expose()).as_bytes(),
+    );
+    let k_service = sha256(service.as_bytes(), &k_date);
     let k_signing = sha256(b"tc3_request", &k_service);
     let signature = hex::encode(sha256(string_to_sign.as_bytes(), &k_signing));
 
     // Step 4: Build Authorization
-    let authorization = format!(
+    format!(
         "{} Credential={}/{}, SignedHeaders={}, Signature={}",
-        algorithm, secret_id, credential_scope, signed_headers, signature
-    );
+        algorithm,
+        credential.secret_id.expose(),
+        credential_scope,
+        signed_headers,
+        signature
+    )
+}
 
-    // Step 5: Create and send request
-    let mut headers = HeaderMap::new();
-    headers.insert("Authorization", HeaderValue::from_str(&authorization)?);
-    headers.insert(
-        "Content-Type",
-        HeaderValue::from_static("application/json; charset=utf-8"),
-    );
-    headers.insert("Host", HeaderValue::from_str(HOST)?);
-    headers.insert("X-TC-Action", HeaderValue::from_str(ACTION)?);
-    headers.insert(
-        "X-TC-Timestamp",
-        HeaderValue::from_str(&timestamp.to_string())?,
-    );
-    headers.insert("X-TC-Version", HeaderValue::from_str(VERSION)?);
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ResponseError {
+    pub code: String,
+    pub message: String,
+}
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Response {
+    pub request_id: String,
+    #[serde(default)]
+    pub target_text_list: Vec<String>,
+    #[serde(default)]
+    pub used_amount: u32,
+    pub error: Option<ResponseError>,
+}
 
-    if !REGION.is_empty() {
-        headers.insert("X-TC-Region", HeaderValue::from_str(REGION)?);
-    }
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RequestResponse {
+    pub response: Response,
+}
 
-    if !TOKEN.is_empty() {
-        headers.insert("X-TC-Token", HeaderValue::from_str(TOKEN)?);
-    }
+// `Response.Error` of a request
+#[derive(Debug)]
+pub struct TencentError {
+    pub code: String,
+    pub message: String,
+    pub request_id: String,
+}
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!("https://{}", HOST))
-        .headers(headers)
-        .body(payload)
-        .send()
-        .await?;
-
-    let response = response.json::<RequestResponse>().await?;
-    if let Some(e) = response.Response.Error {
-        panic!("Request Result Error: {}", e.Message);
+impl fmt::Display for TencentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Request Result Error: [{}] {} (RequestId: {})",
+            self.code, self.message, self.request_id
+        )
     }
-    println!(
-        "=========== Translate {:?} words, use amount {:?}===========",
-        response.Response.TargetTextList.len(),
-        response.Response.UsedAmount
-    );
+}
+
+impl Error for TencentError {}
 
-    return Ok(response);
+impl TencentError {
+    // Rate limits and server side failures pass, everything else, e.g. `AuthFailure.*` or
+    // `FailedOperation.NoFreeAmount`, fails again when retried
+    fn is_retryable(&self) -> bool {
+        [
+            "RequestLimitExceeded",
+            "InternalError",
+            "LimitExceeded.LimitedAccessFrequency",
+        ]
+        .iter()
+        .any(|code| self.code == *code || self.code.starts_with(&format!("{}.", code)))
+    }
 }
 
-pub struct TencentPayload {
-    source: String,
-    target: String,
+// Tencent Cloud Machine Translation, texts are sent with the `TextTranslateBatch` API
+pub struct TencentProvider {
+    credential: TencentCredential,
+    endpoint: TencentEndpoint,
     project_id: u32,
+    client: reqwest::Client,
 }
 
-impl TencentPayload {
-    pub fn new(source: String, target: String, project_id: u32) -> TencentPayload {
-        TencentPayload {
-            source,
-            target,
+impl TencentProvider {
+    pub fn new(
+        credential: TencentCredential,
+        endpoint: TencentEndpoint,
+        project_id: u32,
+    ) -> TencentProvider {
+        TencentProvider {
+            credential,
+            endpoint,
             project_id,
+            client: reqwest::Client::new(),
         }
     }
-}
 
-impl Payload for TencentPayload {
-    fn to_string(&self, pair_list: &Vec<(String, Value)>) -> String {
+    fn get_payload(&self, pair_list: &[(String, Value)], source: &str, target: &str) -> String {
         let arr = pair_list.iter().map(|x| x.1.clone()).collect();
         let mut map = Map::new();
-        map.insert("Source".to_string(), Value::String(self.source.to_string()));
-        map.insert("Target".to_string(), Value::String(self.target.to_string()));
+        map.insert("Source".to_string(), Value::String(source.to_string()));
+        map.insert("Target".to_string(), Value::String(target.to_string()));
         map.insert(
             "ProjectId".to_string(),
             Value::Number(self.project_id.into()),
         );
 
         map.insert("SourceTextList".to_string(), Value::Array(arr));
-        return to_string(&map).unwrap();
+        to_string(&map).unwrap()
     }
-    fn to_map(&self, pair_list: &Vec<(String, Value)>, list: Vec<String>) -> Map<String, Value> {
-        let mut map = Map::new();
-        let mut idx = 0;
-        pair_list.iter().for_each(|x| {
-            map.insert(x.0.to_string(), Value::String(list[idx].to_string()));
-            idx += 1;
-        });
-        return map;
+
+    async fn generate_by_tencent(
+        &self,
+        pair_list: &[(String, Value)],
+        source: &str,
+        target: &str,
+    ) -> Result<BatchResult, Box<dyn Error>> {
+        const SERVICE: &str = "tmt";
+        const ACTION: &str = "TextTranslateBatch";
+        const VERSION: &str = "2018-03-21";
+
+        let credential = &self.credential;
+        let endpoint = &self.endpoint;
+        let timestamp = Utc::now().timestamp();
+
+        let payload = self.get_payload(pair_list, source, target);
+        let authorization = get_authorization(
+            credential,
+            &endpoint.host,
+            endpoint.url.path(),
+            payload.as_bytes(),
+            timestamp,
+            SERVICE,
+        );
+
+        // Create and send request
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", HeaderValue::from_str(&authorization)?);
+        headers.insert("Content-Type", HeaderValue::from_static(CONTENT_TYPE));
+        headers.insert("Host", HeaderValue::from_str(&endpoint.host)?);
+        headers.insert("X-TC-Action", HeaderValue::from_str(ACTION)?);
+        headers.insert(
+            "X-TC-Timestamp",
+            HeaderValue::from_str(&timestamp.to_string())?,
+        );
+        headers.insert("X-TC-Version", HeaderValue::from_str(VERSION)?);
+
+        if !endpoint.region.is_empty() {
+            headers.insert("X-TC-Region", HeaderValue::from_str(&endpoint.region)?);
+        }
+
+        if let Some(token) = &credential.token {
+            headers.insert("X-TC-Token", HeaderValue::from_str(token.expose())?);
+        }
+
+        let response = self
+            .client
+            .post(endpoint.url.clone())
+            .headers(headers)
+            .body(payload)
+            .send()
+            .await?;
+
+        let response = response
+            .error_for_status()?
+            .json::<RequestResponse>()
+            .await?;
+        if let Some(e) = response.response.error {
+            return Err(TencentError {
+                code: e.code,
+                message: e.message,
+                request_id: response.response.request_id,
+            }
+            .into());
+        }
+        if response.response.target_text_list.len() != pair_list.len() {
+            return Err(format!(
+                "Expected {} translated texts but got {} (RequestId: {})",
+                pair_list.len(),
+                response.response.target_text_list.len(),
+                response.response.request_id
+            )
+            .into());
+        }
+        Ok(BatchResult {
+            texts: response
+                .response
+                .target_text_list
+                .into_iter()
+                .map(Some)
+                .collect(),
+            used_amount: response.response.used_amount,
+        })
+    }
+}
+
+impl TranslationProvider for TencentProvider {
+    fn name(&self) -> &str {
+        "Tencent"
+    }
+
+    fn limits(&self) -> ProviderLimits {
+        ProviderLimits {
+            max_len: MAX_BATCH_CHARS,
+            unit: LengthUnit::Chars,
+            max_texts: MAX_BATCH_TEXTS,
+        }
+    }
+
+    // Tencent errors by code, and the retryable HTTP failures
+    fn is_retryable(&self, err: &(dyn Error + 'static)) -> bool {
+        match err.downcast_ref::<TencentError>() {
+            Some(e) => e.is_retryable(),
+            None => is_retryable_http(err),
+        }
+    }
+
+    fn translate<'a>(
+        &'a self,
+        pair_list: &'a [(String, Value)],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, Result<BatchResult, Box<dyn Error>>> {
+        Box::pin(self.generate_by_tencent(pair_list, source, target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translate::credential::Secret;
+
+    // The CVM `DescribeInstances` example of the TC3-HMAC-SHA256 documentation
+    #[test]
+    fn signs_the_documented_example() {
+        let credential = TencentCredential {
+            secret_id: Secret::new("AKIDz8krbsJ5yKBZQpn74WFkmLPx3EXAMPLE".to_string()),
+            secret_key: Secret::new("Gu5t9xGARNpq86cd98joQYCN3EXAMPLE".to_string()),
+            token: None,
+        };
+        let payload = r#"{"Limit": 1, "Filters": [{"Values": ["\u672a\u547d\u540d"], "Name": "instance-name"}]}"#;
+        assert_eq!(
+            get_authorization(
+                &credential,
+                "cvm.tencentcloudapi.com",
+                "/",
+                payload.as_bytes(),
+                1551113065,
+                "cvm"
+            ),
+            "TC3-HMAC-SHA256 Credential=AKIDz8krbsJ5yKBZQpn74WFkmLPx3EXAMPLE/2019-02-25/cvm/tc3_request, \
+             SignedHeaders=content-type;host, \
+             Signature=72e494ea809ad7a8c8f7a4507b9bddcbaa8e581f516e8da2f66e2c5a96525168"
+        );
     }
 }
@@ -1,58 +1,111 @@
+use clap::ValueEnum;
+use serde::Deserialize;
 use serde_json::{from_reader, to_writer_pretty, Map, Value};
 use std::{env, fs::File, path::Path};
 
-use super::tencent::generate_by_tencent;
+use super::provider::{get_batches, TranslationProvider};
+use super::retry::{send_with_retry, RateLimiter, RequestPolicy};
 
-pub trait Payload {
-    fn to_string(&self, pair_list: &Vec<(String, Value)>) -> String;
-    fn to_map(&self, pair_list: &Vec<(String, Value)>, list: Vec<String>) -> Map<String, Value>;
+// Translation services, `translate --provider` or `provider` in the config file
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    /// Tencent Cloud Machine Translation
+    Tencent,
+    /// DeepL API, free or pro plan
+    Deepl,
+    /// OpenAI or a compatible chat completions API, e.g. Ollama or the llama.cpp server
+    Openai,
+    /// Google Cloud Translation v3
+    Google,
+    /// Azure AI Translator
+    Azure,
+    /// Amazon Translate
+    Aws,
 }
 
-pub struct Translate<T: Payload> {
+pub struct TranslateResult {
+    // number of translated texts
+    pub translated: usize,
+    // number of texts the translation service skipped, they are translated by the next run
+    pub skipped: usize,
+    // characters, or tokens of language models, charged by the translation service
+    pub used_amount: u32,
+}
+
+pub struct Translate {
     input: String,
     output: String,
+    source: String,
+    target: String,
     pub pair_list: Vec<(String, Value)>,
-    pub payload: T,
 }
 
-impl<T: Payload> Translate<T> {
-    pub fn new(input: String, output: String, payload: T) -> Translate<T> {
+impl Translate {
+    pub fn new(input: String, output: String, source: String, target: String) -> Translate {
         Translate {
             input,
             output,
-            payload,
+            source,
+            target,
             pair_list: vec![],
         }
     }
-    fn get_pair_list(&self, excludes: &Map<String, Value>) -> Vec<(String, Value)> {
-        let input_path = env::current_dir().unwrap().join(self.input.to_string());
+    fn get_pair_list(
+        &self,
+        excludes: &Map<String, Value>,
+    ) -> Result<Vec<(String, Value)>, Box<dyn std::error::Error>> {
+        let input_path = env::current_dir()?.join(&self.input);
         let file = File::open(&input_path)
-            .expect(format!("Open input file with path {:?} error", input_path).as_str());
+            .map_err(|e| format!("Failed to open input file {:?}: {}", input_path, e))?;
+        let json = from_reader::<File, Value>(file)
+            .map_err(|e| format!("Failed to parse input file {:?}: {}", input_path, e))?;
+        let obj = json
+            .as_object()
+            .ok_or_else(|| format!("Input file {:?} is not a JSON object", input_path))?;
         let mut list = Vec::new();
-        if let Ok(json_str) = from_reader::<File, Value>(file) {
-            let json_obj = json_str.as_object();
-            if let Some(obj) = json_obj {
-                obj.iter().for_each(|x| {
-                    if !excludes.contains_key(x.0) {
-                        list.push((x.0.to_owned(), x.1.to_owned()));
-                    }
-                });
+        obj.iter().for_each(|x| {
+            if !excludes.contains_key(x.0) {
+                list.push((x.0.to_owned(), x.1.to_owned()));
             }
-        }
-        return list;
+        });
+        Ok(list)
+    }
+    // skipped texts are left out
+    fn to_map(
+        &self,
+        pair_list: &[(String, Value)],
+        list: Vec<Option<String>>,
+    ) -> Map<String, Value> {
+        let mut map = Map::new();
+        pair_list.iter().zip(list).for_each(|(x, text)| {
+            if let Some(text) = text {
+                map.insert(x.0.to_string(), Value::String(text));
+            }
+        });
+        map
     }
-    pub fn from_tencent(
+    pub fn run(
         &mut self,
-        secret_id: &str,
-        secret_key: &str,
+        provider: &dyn TranslationProvider,
+        policy: &RequestPolicy,
         write_all: bool,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<TranslateResult, Box<dyn std::error::Error>> {
         let output_path = Path::new(&self.output);
         let mut obj: Map<String, Value> = Map::new();
         match File::open(output_path) {
             Ok(file) => {
-                let obj_str = from_reader::<File, Value>(file).unwrap_or(Value::Object(Map::new()));
-                obj = obj_str.as_object().unwrap().to_owned();
+                // an unparsable file or other JSON values would be overwritten by the translations
+                let obj_str = from_reader::<File, Value>(file)
+                    .map_err(|e| format!("Failed to parse output file {:?}: {}", output_path, e))?;
+                obj = match obj_str {
+                    Value::Object(map) => map,
+                    _ => {
+                        return Err(
+                            format!("Output file {:?} is not a JSON object", output_path).into(),
+                        )
+                    }
+                };
             }
             Err(_) => {
                 println!(
@@ -66,17 +119,78 @@ impl<T: Payload> Translate<T> {
         if !write_all {
             excludes = &obj;
         }
-        self.pair_list = self.get_pair_list(excludes);
-        if self.pair_list.len() == 0 {
+        self.pair_list = self.get_pair_list(excludes)?;
+        if self.pair_list.is_empty() {
             println!("=========== Nothing needs to translate ===========",);
-            return Ok(());
+            return Ok(TranslateResult {
+                translated: 0,
+                skipped: 0,
+                used_amount: 0,
+            });
+        }
+        let batches = get_batches(&self.pair_list, &provider.limits())?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        // batches are sent one by one and their texts are joined in the order of the input
+        let mut target_text_list = Vec::with_capacity(self.pair_list.len());
+        let mut used_amount = 0;
+        let mut limiter = RateLimiter::new(policy.rps);
+        let mut failed = None;
+        for batch in batches {
+            let pair_list = &self.pair_list[batch];
+            let result = runtime.block_on(send_with_retry(
+                policy,
+                &mut limiter,
+                |e| provider.is_retryable(e),
+                || provider.translate(pair_list, &self.source, &self.target),
+            ));
+            match result {
+                Ok(result) if result.texts.len() != pair_list.len() => {
+                    failed = Some(
+                        format!(
+                            "{} returned {} translated texts for {} texts",
+                            provider.name(),
+                            result.texts.len(),
+                            pair_list.len()
+                        )
+                        .into(),
+                    );
+                    break;
+                }
+                Ok(result) => {
+                    used_amount += result.used_amount;
+                    target_text_list.extend(result.texts);
+                }
+                Err(e) => {
+                    failed = Some(e);
+                    break;
+                }
+            }
+        }
+        // texts translated before a failure are kept, so running again only translates the rest
+        let done = &self.pair_list[..target_text_list.len()];
+        let mut json = self.to_map(done, target_text_list);
+        let translated = json.len();
+        let skipped = done.len() - translated;
+        if failed.is_none() || translated > 0 {
+            obj.append(&mut json);
+            to_writer_pretty(File::create(output_path)?, &obj)?;
+        }
+        if let Some(e) = failed {
+            return Err(format!(
+                "{}. {} of {} texts were translated and written to the output, used amount {}",
+                e.to_string().trim_end_matches('.'),
+                translated,
+                self.pair_list.len(),
+                used_amount
+            )
+            .into());
         }
-        let result = generate_by_tencent(&self, &self.pair_list, secret_id, secret_key)?;
-        let mut json = self
-            .payload
-            .to_map(&self.pair_list, result.Response.TargetTextList);
-        obj.append(&mut json);
-        to_writer_pretty(File::create(output_path)?, &obj).unwrap();
-        return Ok(());
+        Ok(TranslateResult {
+            translated,
+            skipped,
+            used_amount,
+        })
     }
 }